@@ -1,3 +1,23 @@
 pub const ELECTION_SEED: &str = "election";
-pub const CHANGABLE_TOKEN_SEED: &str = "changabletoken";
-pub const USER_SEED: &str = "user";
\ No newline at end of file
+pub const USER_SEED: &str = "user";
+pub const DAO_SEED: &str = "dao";
+pub const GOVERNANCE_SEED: &str = "governance";
+pub const VERIFYING_KEY_SEED: &str = "verifyingkey";
+pub const RESULT_SEED: &str = "result";
+pub const TREASURY_SEED: &str = "treasury";
+pub const SPENDING_SEED: &str = "spending";
+pub const STREAM_SEED: &str = "stream";
+pub const NFT_VOTE_SEED: &str = "nftvote";
+
+pub const MAX_NAME_LEN: usize = 32;
+pub const MAX_SYMBOL_LEN: usize = 10;
+pub const MAX_PROPOSAL_INSTRUCTIONS: usize = 4;
+pub const MAX_INSTRUCTION_ACCOUNTS: usize = 8;
+pub const MAX_INSTRUCTION_DATA_LEN: usize = 256;
+pub const MAX_VERIFYING_KEY_LEN: usize = 1536;
+pub const MAX_WEIGHTED_MINTS: usize = 4;
+
+pub const MAX_INSTRUCTION_SIZE: usize = 32 + 4 + MAX_INSTRUCTION_ACCOUNTS * (32 + 1 + 1) + 4 + MAX_INSTRUCTION_DATA_LEN;
+// Largest serialized `ProposalAction` (the `CustomInstruction` variant)
+pub const MAX_ACTION_SIZE: usize = 1 + 4 + MAX_PROPOSAL_INSTRUCTIONS * MAX_INSTRUCTION_SIZE;
+pub const PROPOSAL_RESULT_SIZE: usize = 8 + 32 + 32 + 8 + 32 + 1 + 8 + 8 + 8 + 8 + 4 + 8 + 8 + 8 + 1 + 1;
@@ -18,4 +18,54 @@ pub enum CustomError {
     InvalidPublicInput,
     #[msg("Proof verification failed.")]
     ProofVerificationFailed,
+    #[msg("Invalid proposal action.")]
+    InvalidAction,
+    #[msg("Proposal action is not supported yet.")]
+    ActionNotSupported,
+    #[msg("Proposal action has already been executed.")]
+    ActionAlreadyExecuted,
+    #[msg("Invalid DAO configuration.")]
+    InvalidDaoConfig,
+    #[msg("Proposal did not pass.")]
+    ProposalNotPassed,
+    #[msg("Proposal is not in the required state.")]
+    InvalidProposalState,
+    #[msg("Execution delay has not elapsed.")]
+    TimelockActive,
+    #[msg("Execution delay has already elapsed.")]
+    TimelockElapsed,
+    #[msg("Signer is not authorized for this action.")]
+    Unauthorized,
+    #[msg("Votes have already been cast.")]
+    VotesAlreadyCast,
+    #[msg("Invalid verifying key.")]
+    InvalidVerifyingKey,
+    #[msg("Missing or invalid return data.")]
+    InvalidReturnData,
+    #[msg("Amount must be greater than zero.")]
+    InvalidAmount,
+    #[msg("Treasury accounts are missing or do not match the proposal.")]
+    InvalidTreasuryAccounts,
+    #[msg("Treasury spending cap exceeded.")]
+    SpendingCapExceeded,
+    #[msg("Nothing to claim yet.")]
+    NothingToClaim,
+    #[msg("Stream was already cancelled.")]
+    StreamCancelled,
+    #[msg("Mint accounts are missing or do not match the proposal.")]
+    InvalidMintAccounts,
+    #[msg("Governing mint has a transfer hook or is non-transferable.")]
+    InvalidGoverningMint,
+    #[msg("Instruction does not match the DAO's voting power source.")]
+    WrongVotingPower,
+    #[msg("NFT accounts are missing or not part of the collection.")]
+    InvalidNftAccounts,
+    #[msg("NFT has already voted on this proposal.")]
+    NftAlreadyVoted,
+    #[msg("Token account for this mint was already counted.")]
+    DuplicateVotingMint,
+    #[msg("Vote weight overflows.")]
+    VoteWeightOverflow,
+    #[msg("Voting period has ended.")]
+    VotingClosed,
 }
@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{MintAuthorityType, ProposalAction, ProposalState};
+
+#[event]
+pub struct ProposalCreated {
+    pub election: Pubkey,
+    pub dao: Pubkey,
+    pub id: u64,
+    pub creator: Pubkey,
+    pub action: ProposalAction,
+    pub voting_ends_at: i64,
+}
+
+#[event]
+pub struct VoteCast {
+    pub election: Pubkey,
+    pub voter: Pubkey,
+    pub choice: bool,
+    pub weight: u64,
+}
+
+#[event]
+pub struct ProposalFinalized {
+    pub election: Pubkey,
+    pub id: u64,
+    pub state: ProposalState,
+    pub current: i64,
+    pub number_of_votes: u64,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub election: Pubkey,
+    pub id: u64,
+    pub executor: Pubkey,
+}
+
+#[event]
+pub struct RewardGranted {
+    pub user: Pubkey,
+    pub election: Pubkey,
+    pub reward_points: u64, // Total after this grant
+}
+
+#[event]
+pub struct VerifyingKeyUpdated {
+    pub dao: Pubkey,
+    pub verifying_key: Pubkey,
+    pub authority: Pubkey,
+    pub version: u32,
+}
+
+#[event]
+pub struct ProposalCancelled {
+    pub election: Pubkey,
+    pub id: u64,
+    pub creator: Pubkey,
+}
+
+#[event]
+pub struct ProposalVetoed {
+    pub election: Pubkey,
+    pub id: u64,
+    pub guardian: Pubkey,
+    pub previous_state: ProposalState,
+}
+
+#[event]
+pub struct TreasuryDeposited {
+    pub dao: Pubkey,
+    pub mint: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct TreasuryTransferred {
+    pub dao: Pubkey,
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub epoch_spent: u64, // Total paid out of this mint in the current epoch
+}
+
+#[event]
+pub struct StreamCreated {
+    pub dao: Pubkey,
+    pub stream: Pubkey,
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub start: i64,
+    pub end: i64,
+}
+
+#[event]
+pub struct StreamClaimed {
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub claimed: u64, // Total claimed so far
+}
+
+#[event]
+pub struct StreamCancelled {
+    pub stream: Pubkey,
+    pub vested: u64,
+    pub returned: u64, // Unvested amount released back to the treasury
+}
+
+#[event]
+pub struct DepositSettled {
+    pub election: Pubkey,
+    pub amount: u64,
+    pub refunded: bool, // Returned to the creator, otherwise slashed to the treasury
+}
+
+#[event]
+pub struct TokensMinted {
+    pub dao: Pubkey,
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct MintAuthorityChanged {
+    pub dao: Pubkey,
+    pub mint: Pubkey,
+    pub authority_type: MintAuthorityType,
+    pub new_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct TokenAccountFrozen {
+    pub dao: Pubkey,
+    pub mint: Pubkey,
+    pub account: Pubkey,
+    pub frozen: bool, // False when thawed
+}
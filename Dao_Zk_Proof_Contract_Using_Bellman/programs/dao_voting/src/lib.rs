@@ -1,149 +1,620 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::entrypoint::ProgramResult;
-use anchor_spl::token::{self, TokenAccount};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::system_instruction;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::spl_token::instruction::AuthorityType;
+use anchor_spl::token::{self, FreezeAccount, Mint, MintTo, SetAuthority, ThawAccount, Token, TokenAccount, Transfer};
+use spl_token_2022::extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions};
+use spl_token_2022::state::{Account as Token2022Account, Mint as Token2022Mint};
+use spl_token_group_interface::state::TokenGroupMember;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::{Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount};
+use spl_token_metadata_interface::state::{Field, TokenMetadata};
 use solana_program::account_info::AccountInfo;
-use solana_program::program_pack::Pack;
-use spl_token::state::Account as SplTokenAccount;
 use bellman::groth16::{Proof, prepare_verifying_key, verify_proof};
 use bls12_381::{Bls12, Scalar};
-use std::str::FromStr;
 use bellman::groth16::VerifyingKey as GrothVerifyingKey;
-use anchor_lang::error::AnchorError;
 
-mod constants;
-mod state;
-mod errors;
+pub mod constants;
+pub mod state;
+pub mod errors;
+pub mod events;
+#[cfg(feature = "cpi")]
+pub mod outcome;
 
-use crate::{constants::*, state::*, errors::*};
+use crate::{constants::*, state::*, errors::*, events::*};
 
 declare_id!("3XuNmJEHjuk5Vo7U6fAPp1vJekyW2GJsSmWBWkLjnbyK");
 
 #[program]
 pub mod dao_voting {
-    use std::num::NonZeroI128;
-
     use super::*;
 
-    pub fn new_polling(
-        ctx: Context<NewPolling>,
-        token: Pubkey,
-        proposal_voting: String,
-        value: String,
-        additional_value: String,
-    ) -> Result<()> {
+    // Remaining accounts are the config's weighted mints, in order
+    pub fn initialize_dao(ctx: Context<InitializeDao>, token: Pubkey, config: DaoConfig) -> Result<()> {
+        config.validate()?;
+        check_governing_mint(&ctx.accounts.mint.to_account_info())?;
+        check_weighted_mints(&config, ctx.remaining_accounts)?;
+
+        let dao = &mut ctx.accounts.dao;
+        dao.token = token;
+        dao.config = config;
+        dao.bump = ctx.bumps.dao;
+        Ok(())
+    }
+
+    pub fn set_verifying_key(ctx: Context<SetVerifyingKey>, key: Vec<u8>) -> Result<()> {
+        require!(key.len() <= MAX_VERIFYING_KEY_LEN, CustomError::InvalidVerifyingKey);
+        GrothVerifyingKey::<Bls12>::read(&key[..]).map_err(|_| CustomError::InvalidVerifyingKey)?;
+
+        let verifying_key = &mut ctx.accounts.verifying_key;
+        verifying_key.key = key;
+        verifying_key.version += 1;
+
+        emit!(VerifyingKeyUpdated {
+            dao: ctx.accounts.dao.key(),
+            verifying_key: verifying_key.key(),
+            authority: ctx.accounts.authority.key(),
+            version: verifying_key.version,
+        });
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        require!(amount > 0, CustomError::InvalidAmount);
+
+        let spending = &mut ctx.accounts.spending;
+        spending.dao = ctx.accounts.dao.key();
+        spending.mint = ctx.accounts.mint.key();
+        spending.bump = ctx.bumps.spending;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.depositor_token_account.to_account_info(),
+                    to: ctx.accounts.treasury_token_account.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        emit!(TreasuryDeposited {
+            dao: spending.dao,
+            mint: spending.mint,
+            depositor: ctx.accounts.depositor.key(),
+            amount,
+        });
+        Ok(())
+    }
+
+    pub fn new_polling(ctx: Context<NewPolling>, action: ProposalAction) -> Result<()> {
         let election = &mut ctx.accounts.election;
 
         require!(!election.vote_active, CustomError::VoteActive);
 
-        let balance = get_token_balance(&ctx.accounts.token_account.to_account_info())?;
+        let dao = &ctx.accounts.dao;
+        let token_account = &ctx.accounts.token_account;
+        match dao.config.voting_power {
+            VotingPower::Token => {
+                require_keys_eq!(token_account.mint, dao.token, anchor_lang::error::ErrorCode::ConstraintTokenMint);
+            }
+            VotingPower::NftCollection { collection } => {
+                let nft_mint = ctx.accounts.nft_mint.as_ref().ok_or(CustomError::InvalidNftAccounts)?;
+                require_keys_eq!(token_account.mint, nft_mint.key(), CustomError::InvalidNftAccounts);
+                check_collection_member(&nft_mint.to_account_info(), &collection)?;
+            }
+        }
+        let balance = get_token_balance(&token_account.to_account_info())?;
         require!(balance > 0, CustomError::InsufficientBalance);
 
+        action.validate(&dao.key(), &dao.config)?;
+
         let now = Clock::get().unwrap().unix_timestamp;
         election.id = now as u64; // Unique identifier
-        election.token = token;
-        election.proposal_voting = proposal_voting;
-        election.value = value;
-        election.additional_value = additional_value;
+        election.dao = dao.key();
+        election.token = dao.token;
+        election.action = action;
+        election.executed = false;
+        election.state = ProposalState::Voting;
+        election.executable_at = 0;
+        election.vk_version = ctx.accounts.verifying_key.version;
         election.vote_active = true;
-        election.time = now;
+        election.time = now + dao.config.voting_period;
+        election.min_votes = dao.config.min_votes;
+        election.deposit = dao.config.proposal_deposit;
+        election.voting_power = dao.config.voting_power;
+        election.weighted_mints = dao.config.weighted_mints.clone();
         election.creator = ctx.accounts.authority.key();
+
+        if election.deposit > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: election.to_account_info(),
+                    },
+                ),
+                election.deposit,
+            )?;
+        }
+
+        emit!(ProposalCreated {
+            election: election.key(),
+            dao: election.dao,
+            id: election.id,
+            creator: election.creator,
+            action: election.action.clone(),
+            voting_ends_at: election.time,
+        });
         Ok(())
     }
 
-    pub fn vote(
-        ctx: Context<Vote>, 
+    // Remaining accounts are token accounts of the DAO's weighted mints, at most one per mint
+    pub fn vote<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Vote<'info>>,
         vote: bool, 
         zk_proof: Vec<u8>, 
         public_input: String
     ) -> Result<()> {
         let election = &mut ctx.accounts.election;
         require!(election.vote_active, CustomError::VoteInactive);
+        require!(Clock::get()?.unix_timestamp <= election.time, CustomError::VotingClosed);
+        require!(election.voting_power == VotingPower::Token, CustomError::WrongVotingPower);
     
         let balance = get_token_balance(&ctx.accounts.token_account.to_account_info())?;
+        let weighted = weighted_balance(election, &ctx.accounts.authority.key(), ctx.remaining_accounts)?;
+        let balance = balance.checked_add(weighted).ok_or(CustomError::VoteWeightOverflow)?;
         require!(balance > 0, CustomError::InsufficientBalance);
         require!(!election.voters.contains(&ctx.accounts.authority.key()), CustomError::AlreadyVoted);
     
         verify_zk_proof(zk_proof, public_input, &ctx.accounts.verifying_key.key)?;
 
-        if vote {
-            election.current += balance as i64;
-        } else {
-            election.current -= balance as i64;
-        }
-        election.number_of_votes += 1;
-        election.voters.push(ctx.accounts.authority.key()); // Add voter to the list
+        election.record_vote(ctx.accounts.authority.key(), vote, balance);
 
         // Create or update user account with reward points
         let user = &mut ctx.accounts.user;
         user.pubkey = ctx.accounts.authority.key();
         user.reward_points += 1; // Award 1 reward point for voting
 
+        emit!(VoteCast {
+            election: election.key(),
+            voter: user.pubkey,
+            choice: vote,
+            weight: balance,
+        });
+        emit!(RewardGranted {
+            user: user.pubkey,
+            election: election.key(),
+            reward_points: user.reward_points,
+        });
+        Ok(())
+    }
+
+    // Remaining accounts are (token account, NFT mint, vote marker) triples, one per NFT
+    pub fn vote_nft<'info>(
+        ctx: Context<'_, '_, '_, 'info, VoteNft<'info>>,
+        vote: bool,
+        zk_proof: Vec<u8>,
+        public_input: String
+    ) -> Result<()> {
+        let election = &mut ctx.accounts.election;
+        require!(election.vote_active, CustomError::VoteInactive);
+        require!(Clock::get()?.unix_timestamp <= election.time, CustomError::VotingClosed);
+        let VotingPower::NftCollection { collection } = election.voting_power else {
+            return err!(CustomError::WrongVotingPower);
+        };
+        require!(
+            !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len().is_multiple_of(3),
+            CustomError::InvalidNftAccounts
+        );
+
+        verify_zk_proof(zk_proof, public_input, &ctx.accounts.verifying_key.key)?;
+
+        let voter = ctx.accounts.authority.key();
+        let mut weight = 0;
+        for nft in ctx.remaining_accounts.chunks(3) {
+            let (token_account, mint, marker) = (&nft[0], &nft[1], &nft[2]);
+            check_collection_member(mint, &collection)?;
+            check_nft_holder(token_account, mint.key, &voter)?;
+            create_vote_marker(
+                marker,
+                NftVoteMarker {
+                    election: election.key(),
+                    id: election.id,
+                    mint: mint.key(),
+                    voter,
+                    bump: 0,
+                },
+                &ctx.accounts.authority.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &election.creator,
+            )?;
+            weight += 1;
+        }
+        // NFTs can be split across calls, but the wallet earns its point once per proposal, like `vote`
+        let first_vote = !election.voters.contains(&voter);
+        election.record_vote(voter, vote, weight);
+
+        let user = &mut ctx.accounts.user;
+        user.pubkey = voter;
+        emit!(VoteCast {
+            election: election.key(),
+            voter,
+            choice: vote,
+            weight,
+        });
+        if first_vote {
+            user.reward_points += 1;
+            emit!(RewardGranted {
+                user: voter,
+                election: election.key(),
+                reward_points: user.reward_points,
+            });
+        }
         Ok(())
     }
 
     pub fn to_sum_up(ctx: Context<ToSumUp>) -> Result<()> {
         let election = &mut ctx.accounts.election;
         require!(election.vote_active, CustomError::VoteInactive);
-        require!(Clock::get().unwrap().unix_timestamp > election.time || election.number_of_votes >= election.min_votes, CustomError::VotingTime);
+        require!(election.state == ProposalState::Voting, CustomError::InvalidProposalState);
+        let clock = Clock::get().unwrap();
+        let now = clock.unix_timestamp;
+        require!(now > election.time || election.number_of_votes >= election.min_votes, CustomError::VotingTime);
 
+        // Voting is closed; a passed action is queued behind the DAO's execution delay
+        election.vote_active = false;
         if election.current > 0 {
-            if election.proposal_voting == "newSymbol" {
-                ctx.accounts.changable_token_account.change_symbol(election.value.clone())?;
+            election.state = ProposalState::Succeeded;
+            election.executable_at = now + ctx.accounts.dao.config.execution_delay;
+        } else {
+            election.state = ProposalState::Defeated;
+        }
+
+        // The bond only comes back if enough members bothered to vote
+        if election.number_of_votes >= election.min_votes {
+            settle_deposit(election, &ctx.accounts.creator, true)?;
+        } else {
+            settle_deposit(election, &ctx.accounts.treasury, false)?;
+        }
+
+        ctx.accounts.result.record(election, &clock, ctx.bumps.result);
+
+        emit!(ProposalFinalized {
+            election: election.key(),
+            id: election.id,
+            state: election.state,
+            current: election.current,
+            number_of_votes: election.number_of_votes,
+            executable_at: election.executable_at,
+        });
+        Ok(())
+    }
+
+    // Remaining accounts are those of a `CustomInstruction`, or the weighted mints of a `SetDaoConfig`
+    pub fn execute_proposal<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>) -> Result<()> {
+        let election = &mut ctx.accounts.election;
+        election.start_execution()?;
+        ctx.accounts.result.executed = true;
+
+        match election.action.clone() {
+            ProposalAction::SetSymbol { mint, symbol } => {
+                let dao_key = ctx.accounts.dao.key();
+                let signer_seeds: &[&[u8]] = &[GOVERNANCE_SEED.as_bytes(), dao_key.as_ref(), &[ctx.bumps.governance]];
+                ctx.accounts.governed_mint(mint, signer_seeds)?.change_symbol(symbol)?;
+            }
+            ProposalAction::SetName { mint, name } => {
+                let dao_key = ctx.accounts.dao.key();
+                let signer_seeds: &[&[u8]] = &[GOVERNANCE_SEED.as_bytes(), dao_key.as_ref(), &[ctx.bumps.governance]];
+                ctx.accounts.governed_mint(mint, signer_seeds)?.change_name(name)?;
+            }
+            ProposalAction::SetDaoConfig { config } => {
+                check_weighted_mints(&config, ctx.remaining_accounts)?;
+                ctx.accounts.dao.config = config;
+            }
+            ProposalAction::TransferFromTreasury { mint, amount, recipient } => {
+                ctx.accounts.transfer_from_treasury(mint, amount, recipient, ctx.bumps.treasury)?;
+            }
+            ProposalAction::StreamFromTreasury { mint, amount, recipient, duration } => {
+                ctx.accounts.create_stream(mint, amount, recipient, duration, ctx.bumps.stream)?;
+            }
+            ProposalAction::CancelStream { .. } => {
+                return err!(CustomError::ActionNotSupported);
+            }
+            ProposalAction::MintTo { mint, amount, recipient } => {
+                ctx.accounts.mint_to(mint, amount, recipient, ctx.bumps.governance)?;
             }
-            if election.proposal_voting == "newName" {
-                ctx.accounts.changable_token_account.change_name(election.value.clone())?;
+            ProposalAction::SetMintAuthority { mint, authority_type, new_authority } => {
+                ctx.accounts.set_mint_authority(mint, authority_type, new_authority, ctx.bumps.governance)?;
+            }
+            ProposalAction::FreezeAccount { mint, account } => {
+                ctx.accounts.set_frozen(mint, account, true, ctx.bumps.governance)?;
+            }
+            ProposalAction::ThawAccount { mint, account } => {
+                ctx.accounts.set_frozen(mint, account, false, ctx.bumps.governance)?;
+            }
+            ProposalAction::CustomInstruction { instructions } => {
+                let dao_key = ctx.accounts.dao.key();
+                let signer_seeds: &[&[u8]] = &[GOVERNANCE_SEED.as_bytes(), dao_key.as_ref(), &[ctx.bumps.governance]];
+                for proposal_instruction in instructions {
+                    let instruction = Instruction::from(proposal_instruction);
+                    invoke_signed(&instruction, ctx.remaining_accounts, &[signer_seeds])?;
+                }
             }
         }
 
+        let election = &ctx.accounts.election;
+        emit!(ProposalExecuted {
+            election: election.key(),
+            id: election.id,
+            executor: ctx.accounts.authority.key(),
+        });
         Ok(())
     }
 
-    pub fn get_results(ctx: Context<GetResults>) -> Result<()> {
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
         let election = &mut ctx.accounts.election;
+        require!(election.state == ProposalState::Voting, CustomError::InvalidProposalState);
+        require!(election.number_of_votes == 0, CustomError::VotesAlreadyCast);
 
-        msg!("Total number of votes : {}", election.number_of_votes);
-        msg!("Current Votes Balance : {}", election.current);
+        election.state = ProposalState::Cancelled;
+        election.vote_active = false;
+        settle_deposit(election, &ctx.accounts.treasury, false)?;
+        ctx.accounts.result.record(election, &Clock::get()?, ctx.bumps.result);
 
-        if election.current > 0 {
-            msg!("Proposal Passed");
+        emit!(ProposalCancelled {
+            election: election.key(),
+            id: election.id,
+            creator: election.creator,
+        });
+        Ok(())
+    }
+
+    pub fn veto_proposal(ctx: Context<VetoProposal>) -> Result<()> {
+        let election = &mut ctx.accounts.election;
+        let previous_state = election.state;
+        match previous_state {
+            ProposalState::Voting => {}
+            ProposalState::Succeeded => {
+                require!(!election.executed, CustomError::ActionAlreadyExecuted);
+                require!(Clock::get().unwrap().unix_timestamp < election.executable_at, CustomError::TimelockElapsed);
+            }
+            _ => return err!(CustomError::InvalidProposalState),
+        }
+
+        election.state = ProposalState::Vetoed;
+        election.vote_active = false;
+        // A succeeded proposal already settled its deposit and wrote its result at finalization
+        settle_deposit(election, &ctx.accounts.treasury, false)?;
+        let result = &mut ctx.accounts.result;
+        if previous_state == ProposalState::Voting {
+            result.record(election, &Clock::get()?, ctx.bumps.result);
         } else {
-            msg!("Proposal Rejected");
+            result.state = ProposalState::Vetoed;
         }
 
-        election.reset();
+        emit!(ProposalVetoed {
+            election: election.key(),
+            id: election.id,
+            guardian: ctx.accounts.guardian.key(),
+            previous_state,
+        });
         Ok(())
     }
 
-    pub fn close_election(ctx: Context<CloseElection>) -> Result<()> {
+    pub fn claim_stream(ctx: Context<ClaimStream>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+        let amount = stream.vested(Clock::get()?.unix_timestamp) - stream.claimed;
+        require!(amount > 0, CustomError::NothingToClaim);
+        stream.claimed += amount;
+        ctx.accounts.spending.reserved -= amount;
+
+        let dao_key = ctx.accounts.dao.key();
+        let signer_seeds: &[&[u8]] = &[TREASURY_SEED.as_bytes(), dao_key.as_ref(), &[ctx.bumps.treasury]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.treasury_token_account.to_account_info(),
+                    to: ctx.accounts.recipient.to_account_info(),
+                    authority: ctx.accounts.treasury.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount,
+        )?;
+
+        let stream = &ctx.accounts.stream;
+        emit!(StreamClaimed {
+            stream: stream.key(),
+            recipient: stream.recipient,
+            amount,
+            claimed: stream.claimed,
+        });
+        Ok(())
+    }
+
+    pub fn cancel_stream(ctx: Context<CancelStream>) -> Result<()> {
         let election = &mut ctx.accounts.election;
+        election.start_execution()?;
+        ctx.accounts.result.executed = true;
+        let stream = &mut ctx.accounts.stream;
+        require!(
+            election.action == ProposalAction::CancelStream { stream: stream.key() },
+            CustomError::InvalidAction
+        );
+        require!(!stream.cancelled, CustomError::StreamCancelled);
+
+        // Whatever has vested stays claimable; the rest goes back to the treasury
+        let now = Clock::get()?.unix_timestamp;
+        let vested = stream.vested(now);
+        let returned = stream.total - vested;
+        stream.total = vested;
+        stream.end = stream.end.min(now);
+        stream.cancelled = true;
+        ctx.accounts.spending.reserved -= returned;
+
+        emit!(StreamCancelled {
+            stream: stream.key(),
+            vested,
+            returned,
+        });
+        let election = &ctx.accounts.election;
+        emit!(ProposalExecuted {
+            election: election.key(),
+            id: election.id,
+            executor: ctx.accounts.authority.key(),
+        });
+        Ok(())
+    }
+
+    pub fn get_results(ctx: Context<GetResults>) -> Result<()> {
+        let election = &ctx.accounts.election;
+
+        msg!("Total number of votes : {}", election.number_of_votes);
+        msg!("Current Votes Balance : {}", election.current);
+
+        match election.state {
+            ProposalState::Voting => msg!("Voting in progress"),
+            ProposalState::Succeeded => msg!("Proposal Passed"),
+            ProposalState::Defeated => msg!("Proposal Rejected"),
+            ProposalState::Cancelled => msg!("Proposal Cancelled"),
+            ProposalState::Vetoed => msg!("Proposal Vetoed"),
+        }
+        Ok(())
+    }
+
+    pub fn view_result(ctx: Context<ViewResult>) -> Result<ProposalResult> {
+        Ok(ctx.accounts.result.clone().into_inner())
+    }
+
+    pub fn close_election(ctx: Context<CloseElection>) -> Result<()> {
+        let election = &ctx.accounts.election;
         require!(!election.vote_active, CustomError::VoteActive);
+        // A queued proposal has to stay open until it runs, or the guardian vetoes it
+        require!(election.is_settled(), CustomError::InvalidProposalState);
 
         Ok(())
     }
 }
 
+#[derive(Accounts)]
+#[instruction(token: Pubkey)]
+pub struct InitializeDao<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 8 + 8 + 32 + 8 + 8 + 8 + 33 + 4 + MAX_WEIGHTED_MINTS * (32 + 8) + 1,
+        seeds = [DAO_SEED.as_bytes(), token.as_ref()],
+        bump
+    )]
+    pub dao: Account<'info, Dao>,
+    // Classic SPL or Token-2022. Only its mint authority can claim the DAO address for it.
+    #[account(
+        address = token,
+        constraint = mint.mint_authority == COption::Some(authority.key()) @ CustomError::Unauthorized
+    )]
+    pub mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetVerifyingKey<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 4 + MAX_VERIFYING_KEY_LEN + 4,
+        seeds = [VERIFYING_KEY_SEED.as_bytes(), dao.key().as_ref()],
+        bump
+    )]
+    pub verifying_key: Account<'info, VerifyingKey>,
+    #[account(
+        seeds = [DAO_SEED.as_bytes(), dao.token.as_ref()],
+        bump = dao.bump,
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(mut, address = dao.config.guardian @ CustomError::Unauthorized)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(
+        seeds = [DAO_SEED.as_bytes(), dao.token.as_ref()],
+        bump = dao.bump,
+    )]
+    pub dao: Account<'info, Dao>,
+    /// CHECK: PDA that owns the DAO's treasury token accounts
+    #[account(
+        seeds = [TREASURY_SEED.as_bytes(), dao.key().as_ref()],
+        bump,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 1,
+        seeds = [SPENDING_SEED.as_bytes(), dao.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub spending: Account<'info, TreasurySpending>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = depositor,
+    )]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct NewPolling<'info> {
     #[account(
         init,
         payer = authority, 
-        space = 8 + 64 + 256 + 256 + 8 + 8 + 8 + 1 + 32 + 8 + 1024,
+        space = 8 + 8 + 32 + 32 + MAX_ACTION_SIZE + 1 + 1 + 8 + 4 + 8 + 8 + 8 + 8 + 1 + 8 + 8 + 8 + 8 + 33
+            + 4 + MAX_WEIGHTED_MINTS * (32 + 8) + 32 + 1024,
         seeds = [ELECTION_SEED.as_bytes(), authority.key().as_ref()],
         bump
     )]
     pub election: Account<'info, Election>,
+    #[account(
+        seeds = [DAO_SEED.as_bytes(), dao.token.as_ref()],
+        bump = dao.bump,
+    )]
+    pub dao: Account<'info, Dao>,
     #[account(mut)]
     pub authority: Signer<'info>,
-    #[account(mut)]
-    pub token_account: Account<'info, TokenAccount>,
+    // Holds the governing mint, or an NFT of the collection for NFT DAOs
+    #[account(token::authority = authority)]
+    pub token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+    // Only needed for NFT DAOs
+    pub nft_mint: Option<InterfaceAccount<'info, InterfaceMint>>,
     #[account(
-        init, 
-        payer = authority, 
-        space = 8 + 32 + 256 + 256 + 8 + 8 + 32,
-        seeds = [CHANGABLE_TOKEN_SEED.as_bytes(), authority.key().as_ref()],
-        bump
+        seeds = [VERIFYING_KEY_SEED.as_bytes(), dao.key().as_ref()],
+        bump,
     )]
-    pub changable_token_account: Account<'info, ChangableTokenAccount>, // Initialize the token account
     pub verifying_key: Account<'info, VerifyingKey>,
     pub system_program: Program<'info, System>,
 }
@@ -158,14 +629,37 @@ pub struct Vote<'info> {
     pub election: Account<'info, Election>,
     #[account(mut)]
     pub authority: Signer<'info>,
-    #[account(mut)]
-    pub token_account: Account<'info, TokenAccount>,
+    #[account(
+        token::mint = election.token,
+        token::authority = authority,
+    )]
+    pub token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 8,
+        seeds = [USER_SEED.as_bytes(), authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, User>,
+    #[account(
+        seeds = [VERIFYING_KEY_SEED.as_bytes(), election.dao.as_ref()],
+        bump,
+    )]
+    pub verifying_key: Account<'info, VerifyingKey>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VoteNft<'info> {
     #[account(
         mut,
-        seeds = [CHANGABLE_TOKEN_SEED.as_bytes(), election.creator.as_ref()],
+        seeds = [ELECTION_SEED.as_bytes(), election.creator.as_ref()],
         bump,
     )]
-    pub changable_token_account: Account<'info, ChangableTokenAccount>,
+    pub election: Account<'info, Election>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
     #[account(
         init_if_needed,
         payer = authority,
@@ -174,6 +668,10 @@ pub struct Vote<'info> {
         bump
     )]
     pub user: Account<'info, User>,
+    #[account(
+        seeds = [VERIFYING_KEY_SEED.as_bytes(), election.dao.as_ref()],
+        bump,
+    )]
     pub verifying_key: Account<'info, VerifyingKey>,
     pub system_program: Program<'info, System>,
 }
@@ -184,63 +682,541 @@ pub struct ToSumUp<'info> {
         mut,
         seeds = [ELECTION_SEED.as_bytes(), election.creator.as_ref()],
         bump,
+        has_one = dao,
     )]
     pub election: Account<'info, Election>,
+    #[account(
+        seeds = [DAO_SEED.as_bytes(), dao.token.as_ref()],
+        bump = dao.bump,
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        init,
+        payer = authority,
+        space = PROPOSAL_RESULT_SIZE,
+        seeds = [RESULT_SEED.as_bytes(), election.creator.as_ref(), &election.id.to_le_bytes()],
+        bump
+    )]
+    pub result: Account<'info, ProposalResult>,
+    /// CHECK: Receives the refunded deposit
+    #[account(mut, address = election.creator)]
+    pub creator: UncheckedAccount<'info>,
+    /// CHECK: PDA that receives slashed deposits
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED.as_bytes(), dao.key().as_ref()],
+        bump,
+    )]
+    pub treasury: UncheckedAccount<'info>,
     #[account(mut)]
     pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        mut,
+        seeds = [ELECTION_SEED.as_bytes(), election.creator.as_ref()],
+        bump,
+        has_one = dao,
+    )]
+    pub election: Account<'info, Election>,
+    #[account(
+        mut,
+        seeds = [RESULT_SEED.as_bytes(), election.creator.as_ref(), &election.id.to_le_bytes()],
+        bump = result.bump,
+    )]
+    pub result: Account<'info, ProposalResult>,
+    #[account(
+        mut,
+        seeds = [DAO_SEED.as_bytes(), dao.token.as_ref()],
+        bump = dao.bump,
+    )]
+    pub dao: Account<'info, Dao>,
+    /// CHECK: PDA that signs the proposal's instructions on behalf of the DAO
+    #[account(
+        seeds = [GOVERNANCE_SEED.as_bytes(), dao.key().as_ref()],
+        bump,
+    )]
+    pub governance: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    // Only needed for treasury and mint actions
+    /// CHECK: PDA that owns the DAO's treasury token accounts
+    #[account(
+        seeds = [TREASURY_SEED.as_bytes(), dao.key().as_ref()],
+        bump,
+    )]
+    pub treasury: Option<UncheckedAccount<'info>>,
+    #[account(mut, has_one = dao)]
+    pub spending: Option<Account<'info, TreasurySpending>>,
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+    // Token account paid or minted to, or the one being frozen or thawed
     #[account(mut)]
-    pub token_account: Account<'info, TokenAccount>,
+    pub recipient: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub mint: Option<Account<'info, Mint>>,
+    pub token_program: Option<Program<'info, Token>>,
+    // Only needed for `StreamFromTreasury`
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 1,
+        seeds = [STREAM_SEED.as_bytes(), election.creator.as_ref(), &election.id.to_le_bytes()],
+        bump
+    )]
+    pub stream: Option<Account<'info, Stream>>,
+    // Only needed for `SetName` and `SetSymbol`
+    #[account(mut)]
+    pub metadata_mint: Option<InterfaceAccount<'info, InterfaceMint>>,
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+}
+
+impl<'info> ExecuteProposal<'info> {
+    fn transfer_from_treasury(&mut self, mint: Pubkey, amount: u64, recipient: Pubkey, treasury_bump: u8) -> Result<()> {
+        let (Some(treasury), Some(spending), Some(treasury_token_account), Some(recipient_account), Some(token_program)) = (
+            &self.treasury,
+            &mut self.spending,
+            &self.treasury_token_account,
+            &self.recipient,
+            &self.token_program,
+        ) else {
+            return err!(CustomError::InvalidTreasuryAccounts);
+        };
+        check_treasury_accounts(treasury, spending, treasury_token_account, mint, amount)?;
+        require_keys_eq!(recipient_account.key(), recipient, CustomError::InvalidTreasuryAccounts);
+        let spent = spending.charge(&self.dao.config, amount)?;
+
+        let dao_key = self.dao.key();
+        let signer_seeds: &[&[u8]] = &[TREASURY_SEED.as_bytes(), dao_key.as_ref(), &[treasury_bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: treasury_token_account.to_account_info(),
+                    to: recipient_account.to_account_info(),
+                    authority: treasury.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount,
+        )?;
+
+        emit!(TreasuryTransferred {
+            dao: dao_key,
+            mint,
+            recipient,
+            amount,
+            epoch_spent: spent,
+        });
+        Ok(())
+    }
+
+    fn create_stream(&mut self, mint: Pubkey, amount: u64, recipient: Pubkey, duration: i64, stream_bump: u8) -> Result<()> {
+        let (Some(treasury), Some(spending), Some(treasury_token_account), Some(stream)) =
+            (&self.treasury, &mut self.spending, &self.treasury_token_account, &mut self.stream)
+        else {
+            return err!(CustomError::InvalidTreasuryAccounts);
+        };
+        check_treasury_accounts(treasury, spending, treasury_token_account, mint, amount)?;
+        // The whole grant counts against the caps now and stays reserved until claimed or cancelled
+        spending.charge(&self.dao.config, amount)?;
+        spending.reserved += amount;
+
+        let start = Clock::get()?.unix_timestamp;
+        stream.dao = self.dao.key();
+        stream.mint = mint;
+        stream.recipient = recipient;
+        stream.total = amount;
+        stream.claimed = 0;
+        stream.start = start;
+        stream.end = start + duration;
+        stream.cancelled = false;
+        stream.bump = stream_bump;
+
+        emit!(StreamCreated {
+            dao: stream.dao,
+            stream: stream.key(),
+            mint,
+            recipient,
+            amount,
+            start: stream.start,
+            end: stream.end,
+        });
+        Ok(())
+    }
+
+    fn mint_to(&self, mint: Pubkey, amount: u64, recipient: Pubkey, governance_bump: u8) -> Result<()> {
+        let (mint_account, token_program) = self.mint_accounts(mint)?;
+        let Some(recipient_account) = &self.recipient else {
+            return err!(CustomError::InvalidMintAccounts);
+        };
+        require_keys_eq!(recipient_account.key(), recipient, CustomError::InvalidMintAccounts);
+
+        let dao_key = self.dao.key();
+        let signer_seeds: &[&[u8]] = &[GOVERNANCE_SEED.as_bytes(), dao_key.as_ref(), &[governance_bump]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                MintTo {
+                    mint: mint_account.to_account_info(),
+                    to: recipient_account.to_account_info(),
+                    authority: self.governance.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount,
+        )?;
+
+        emit!(TokensMinted {
+            dao: dao_key,
+            mint,
+            recipient,
+            amount,
+        });
+        Ok(())
+    }
+
+    fn set_mint_authority(
+        &self,
+        mint: Pubkey,
+        authority_type: MintAuthorityType,
+        new_authority: Option<Pubkey>,
+        governance_bump: u8,
+    ) -> Result<()> {
+        let (mint_account, token_program) = self.mint_accounts(mint)?;
+
+        let dao_key = self.dao.key();
+        let signer_seeds: &[&[u8]] = &[GOVERNANCE_SEED.as_bytes(), dao_key.as_ref(), &[governance_bump]];
+        token::set_authority(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                SetAuthority {
+                    current_authority: self.governance.to_account_info(),
+                    account_or_mint: mint_account.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            authority_type.into(),
+            new_authority,
+        )?;
+
+        emit!(MintAuthorityChanged {
+            dao: dao_key,
+            mint,
+            authority_type,
+            new_authority,
+        });
+        Ok(())
+    }
+
+    fn set_frozen(&self, mint: Pubkey, account: Pubkey, frozen: bool, governance_bump: u8) -> Result<()> {
+        let (mint_account, token_program) = self.mint_accounts(mint)?;
+        let Some(token_account) = &self.recipient else {
+            return err!(CustomError::InvalidMintAccounts);
+        };
+        require_keys_eq!(token_account.key(), account, CustomError::InvalidMintAccounts);
+
+        let dao_key = self.dao.key();
+        let signer_seeds: &[&[u8]] = &[GOVERNANCE_SEED.as_bytes(), dao_key.as_ref(), &[governance_bump]];
+        if frozen {
+            token::freeze_account(CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                FreezeAccount {
+                    account: token_account.to_account_info(),
+                    mint: mint_account.to_account_info(),
+                    authority: self.governance.to_account_info(),
+                },
+                &[signer_seeds],
+            ))?;
+        } else {
+            token::thaw_account(CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                ThawAccount {
+                    account: token_account.to_account_info(),
+                    mint: mint_account.to_account_info(),
+                    authority: self.governance.to_account_info(),
+                },
+                &[signer_seeds],
+            ))?;
+        }
+
+        emit!(TokenAccountFrozen {
+            dao: dao_key,
+            mint,
+            account,
+            frozen,
+        });
+        Ok(())
+    }
+
+    fn governed_mint<'a>(&self, mint: Pubkey, signer_seeds: &'a [&'a [u8]]) -> Result<GovernedMint<'a, 'info>> {
+        let (Some(metadata_mint), Some(token_program)) = (&self.metadata_mint, &self.token_2022_program) else {
+            return err!(CustomError::InvalidMintAccounts);
+        };
+        require_keys_eq!(metadata_mint.key(), mint, CustomError::InvalidMintAccounts);
+        require_keys_eq!(*metadata_mint.to_account_info().owner, Token2022::id(), CustomError::InvalidMintAccounts);
+        {
+            let mint_info = metadata_mint.to_account_info();
+            let data = mint_info.try_borrow_data()?;
+            let state = StateWithExtensions::<Token2022Mint>::unpack(&data)?;
+            state
+                .get_variable_len_extension::<TokenMetadata>()
+                .map_err(|_| error!(CustomError::InvalidMintAccounts))?;
+        }
+
+        Ok(GovernedMint {
+            mint: metadata_mint.to_account_info(),
+            update_authority: self.governance.to_account_info(),
+            payer: self.authority.to_account_info(),
+            token_program: token_program.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            signer_seeds,
+        })
+    }
+
+    fn mint_accounts(&self, mint: Pubkey) -> Result<(&Account<'info, Mint>, &Program<'info, Token>)> {
+        let (Some(mint_account), Some(token_program)) = (&self.mint, &self.token_program) else {
+            return err!(CustomError::InvalidMintAccounts);
+        };
+        require_keys_eq!(mint_account.key(), mint, CustomError::InvalidMintAccounts);
+        Ok((mint_account, token_program))
+    }
+}
+
+impl From<MintAuthorityType> for AuthorityType {
+    fn from(authority_type: MintAuthorityType) -> Self {
+        match authority_type {
+            MintAuthorityType::MintTokens => AuthorityType::MintTokens,
+            MintAuthorityType::FreezeAccount => AuthorityType::FreezeAccount,
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClaimStream<'info> {
     #[account(
         mut,
-        seeds = [CHANGABLE_TOKEN_SEED.as_bytes(), election.creator.as_ref()],
+        has_one = dao,
+        has_one = recipient,
+    )]
+    pub stream: Account<'info, Stream>,
+    #[account(
+        seeds = [DAO_SEED.as_bytes(), dao.token.as_ref()],
+        bump = dao.bump,
+    )]
+    pub dao: Account<'info, Dao>,
+    /// CHECK: PDA that owns the DAO's treasury token accounts
+    #[account(
+        seeds = [TREASURY_SEED.as_bytes(), dao.key().as_ref()],
         bump,
     )]
-    pub changable_token_account: Account<'info, ChangableTokenAccount>,
+    pub treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [SPENDING_SEED.as_bytes(), dao.key().as_ref(), stream.mint.as_ref()],
+        bump = spending.bump,
+    )]
+    pub spending: Account<'info, TreasurySpending>,
+    #[account(
+        mut,
+        token::mint = stream.mint,
+        token::authority = treasury,
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub recipient: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct GetResults<'info> {
+pub struct CancelStream<'info> {
     #[account(
         mut,
         seeds = [ELECTION_SEED.as_bytes(), election.creator.as_ref()],
         bump,
+        has_one = dao,
     )]
     pub election: Account<'info, Election>,
+    #[account(
+        mut,
+        seeds = [RESULT_SEED.as_bytes(), election.creator.as_ref(), &election.id.to_le_bytes()],
+        bump = result.bump,
+    )]
+    pub result: Account<'info, ProposalResult>,
+    #[account(
+        seeds = [DAO_SEED.as_bytes(), dao.token.as_ref()],
+        bump = dao.bump,
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(mut, has_one = dao)]
+    pub stream: Account<'info, Stream>,
+    #[account(
+        mut,
+        seeds = [SPENDING_SEED.as_bytes(), dao.key().as_ref(), stream.mint.as_ref()],
+        bump = spending.bump,
+    )]
+    pub spending: Account<'info, TreasurySpending>,
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct CloseElection<'info> {
+pub struct CancelProposal<'info> {
     #[account(
         mut,
-        close = authority,
         seeds = [ELECTION_SEED.as_bytes(), election.creator.as_ref()],
-        bump
+        bump,
+        has_one = creator @ CustomError::Unauthorized,
     )]
     pub election: Account<'info, Election>,
+    #[account(
+        init,
+        payer = creator,
+        space = PROPOSAL_RESULT_SIZE,
+        seeds = [RESULT_SEED.as_bytes(), election.creator.as_ref(), &election.id.to_le_bytes()],
+        bump
+    )]
+    pub result: Account<'info, ProposalResult>,
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub creator: Signer<'info>,
+    /// CHECK: PDA that receives slashed deposits
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED.as_bytes(), election.dao.as_ref()],
+        bump,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VetoProposal<'info> {
+    #[account(
+        mut,
+        seeds = [ELECTION_SEED.as_bytes(), election.creator.as_ref()],
+        bump,
+        has_one = dao,
+    )]
+    pub election: Account<'info, Election>,
+    #[account(
+        seeds = [DAO_SEED.as_bytes(), dao.token.as_ref()],
+        bump = dao.bump,
+    )]
+    pub dao: Account<'info, Dao>,
+    // Already there when a succeeded proposal is vetoed during its delay
+    #[account(
+        init_if_needed,
+        payer = guardian,
+        space = PROPOSAL_RESULT_SIZE,
+        seeds = [RESULT_SEED.as_bytes(), election.creator.as_ref(), &election.id.to_le_bytes()],
+        bump
+    )]
+    pub result: Account<'info, ProposalResult>,
+    #[account(mut, address = dao.config.guardian @ CustomError::Unauthorized)]
+    pub guardian: Signer<'info>,
+    /// CHECK: PDA that receives slashed deposits
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED.as_bytes(), dao.key().as_ref()],
+        bump,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetResults<'info> {
+    #[account(
+        seeds = [ELECTION_SEED.as_bytes(), election.creator.as_ref()],
+        bump,
+    )]
+    pub election: Account<'info, Election>,
+}
+
+#[derive(Accounts)]
+pub struct ViewResult<'info> {
+    #[account(
+        seeds = [RESULT_SEED.as_bytes(), result.creator.as_ref(), &result.id.to_le_bytes()],
+        bump = result.bump,
+    )]
+    pub result: Account<'info, ProposalResult>,
+}
+
+#[derive(Accounts)]
+pub struct CloseElection<'info> {
     #[account(
         mut,
-        close = authority,
-        seeds = [CHANGABLE_TOKEN_SEED.as_bytes(), election.creator.as_ref()],
+        close = creator,
+        has_one = creator @ CustomError::Unauthorized,
+        seeds = [ELECTION_SEED.as_bytes(), election.creator.as_ref()],
         bump
     )]
-    pub changable_token_account: Account<'info, ChangableTokenAccount>,
+    pub election: Account<'info, Election>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
 }
 
-impl ChangableToken for ChangableTokenAccount {
+// Token-2022 mint whose metadata extension is the DAO's record of the token's name and symbol
+pub struct GovernedMint<'a, 'info> {
+    pub mint: AccountInfo<'info>,
+    pub update_authority: AccountInfo<'info>, // Governance PDA
+    pub payer: AccountInfo<'info>, // Tops up rent when the metadata grows
+    pub token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub signer_seeds: &'a [&'a [u8]],
+}
+
+impl GovernedMint<'_, '_> {
+    fn update_field(&self, field: Field, value: String) -> ProgramResult {
+        // Token-2022 reallocates the mint to fit the new value, but leaves the rent to the caller
+        let new_len = {
+            let data = self.mint.try_borrow_data()?;
+            let state = StateWithExtensions::<Token2022Mint>::unpack(&data)?;
+            let mut metadata = state.get_variable_len_extension::<TokenMetadata>()?;
+            let old_size = metadata.tlv_size_of()?;
+            metadata.update(field.clone(), value.clone());
+            data.len() - old_size + metadata.tlv_size_of()?
+        };
+        let required = Rent::get()?.minimum_balance(new_len);
+        let lamports = self.mint.lamports();
+        if required > lamports {
+            invoke(
+                &system_instruction::transfer(self.payer.key, self.mint.key, required - lamports),
+                &[self.payer.clone(), self.mint.clone(), self.system_program.clone()],
+            )?;
+        }
+
+        invoke_signed(
+            &spl_token_metadata_interface::instruction::update_field(
+                self.token_program.key,
+                self.mint.key,
+                self.update_authority.key,
+                field,
+                value,
+            ),
+            &[self.mint.clone(), self.update_authority.clone(), self.token_program.clone()],
+            &[self.signer_seeds],
+        )
+    }
+}
+
+impl ChangableToken for GovernedMint<'_, '_> {
     fn change_symbol(&mut self, symbol: String) -> ProgramResult {
-        self.symbol = symbol;
-        Ok(())
+        self.update_field(Field::Symbol, symbol)
     }
 
     fn change_name(&mut self, name: String) -> ProgramResult {
-        self.name = name;
-        Ok(())
+        self.update_field(Field::Name, name)
     }
 
+    // Total supply of the mint, read from the account rather than cached
     fn balance_of(&self, _user: Pubkey) -> Result<u64> {
-        Ok(self.balance)
+        let data = self.mint.try_borrow_data()?;
+        let state = StateWithExtensions::<Token2022Mint>::unpack(&data)?;
+        Ok(state.base.supply)
     }
 }
 
@@ -253,12 +1229,131 @@ pub trait ChangableToken {
 #[account]
 pub struct VerifyingKey {
     pub key: Vec<u8>,
+    pub version: u32, // Bumped on every update
 }
 
+// Classic token accounts unpack as Token-2022 accounts without extensions
 fn get_token_balance(account: &AccountInfo) -> Result<u64> {
     let data = &account.try_borrow_data()?;
-    let token_account = SplTokenAccount::unpack(data)?;
-    Ok(token_account.amount)
+    let token_account = StateWithExtensions::<Token2022Account>::unpack(data)?;
+    Ok(token_account.base.amount)
+}
+
+// Transfer hooks and non-transferable tokens would break the escrow the DAO relies on
+fn check_governing_mint(mint: &AccountInfo) -> Result<()> {
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Token2022Mint>::unpack(&data)?;
+    let extensions = state.get_extension_types()?;
+    require!(
+        !extensions.contains(&ExtensionType::TransferHook) && !extensions.contains(&ExtensionType::NonTransferable),
+        CustomError::InvalidGoverningMint
+    );
+    Ok(())
+}
+
+// Weighted mints count toward votes, so they need the same checks as the governing mint
+fn check_weighted_mints(config: &DaoConfig, mints: &[AccountInfo]) -> Result<()> {
+    require!(mints.len() == config.weighted_mints.len(), CustomError::InvalidMintAccounts);
+    for (weighted_mint, mint) in config.weighted_mints.iter().zip(mints) {
+        require_keys_eq!(weighted_mint.mint, mint.key(), CustomError::InvalidMintAccounts);
+        require!(
+            *mint.owner == token::ID || *mint.owner == Token2022::id(),
+            CustomError::InvalidMintAccounts
+        );
+        check_governing_mint(mint)?;
+    }
+    Ok(())
+}
+
+// Checks each token account like `Vote` checks `token_account`, and the governing mint can't be counted again
+fn weighted_balance<'info>(election: &Election, authority: &Pubkey, token_accounts: &'info [AccountInfo<'info>]) -> Result<u64> {
+    let mut counted = vec![election.token];
+    let mut total: u64 = 0;
+    for info in token_accounts {
+        let token_account = InterfaceAccount::<InterfaceTokenAccount>::try_from(info)?;
+        require_keys_eq!(token_account.owner, *authority, anchor_lang::error::ErrorCode::ConstraintTokenOwner);
+        require!(!counted.contains(&token_account.mint), CustomError::DuplicateVotingMint);
+        let weighted_mint = election
+            .weighted_mints
+            .iter()
+            .find(|weighted_mint| weighted_mint.mint == token_account.mint)
+            .ok_or(anchor_lang::error::ErrorCode::ConstraintTokenMint)?;
+        counted.push(token_account.mint);
+
+        total = token_account
+            .amount
+            .checked_mul(weighted_mint.weight)
+            .and_then(|weight| total.checked_add(weight))
+            .ok_or(CustomError::VoteWeightOverflow)?;
+    }
+    Ok(total)
+}
+
+// NFTs are Token-2022 mints of a single token, enrolled as members of the collection's token group
+fn check_collection_member(mint: &AccountInfo, collection: &Pubkey) -> Result<()> {
+    require_keys_eq!(*mint.owner, Token2022::id(), CustomError::InvalidNftAccounts);
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Token2022Mint>::unpack(&data)?;
+    require!(state.base.supply == 1 && state.base.decimals == 0, CustomError::InvalidNftAccounts);
+    let member = state
+        .get_extension::<TokenGroupMember>()
+        .map_err(|_| CustomError::InvalidNftAccounts)?;
+    require!(
+        member.mint == *mint.key && member.group == *collection,
+        CustomError::InvalidNftAccounts
+    );
+    Ok(())
+}
+
+fn check_nft_holder(token_account: &AccountInfo, mint: &Pubkey, voter: &Pubkey) -> Result<()> {
+    require_keys_eq!(*token_account.owner, Token2022::id(), CustomError::InvalidNftAccounts);
+    let data = token_account.try_borrow_data()?;
+    let state = StateWithExtensions::<Token2022Account>::unpack(&data)?;
+    require!(
+        state.base.mint == *mint && state.base.owner == *voter && state.base.amount == 1,
+        CustomError::InvalidNftAccounts
+    );
+    Ok(())
+}
+
+// The marker is keyed by proposal and NFT, not by voter, so a moved NFT still finds it
+fn create_vote_marker<'info>(
+    marker: &AccountInfo<'info>,
+    mut state: NftVoteMarker,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    creator: &Pubkey,
+) -> Result<()> {
+    let id = state.id.to_le_bytes();
+    let seeds = [NFT_VOTE_SEED.as_bytes(), creator.as_ref(), &id, state.mint.as_ref()];
+    let (address, bump) = Pubkey::find_program_address(&seeds, &crate::ID);
+    require_keys_eq!(marker.key(), address, CustomError::InvalidNftAccounts);
+    require!(marker.owner == &System::id() && marker.data_is_empty(), CustomError::NftAlreadyVoted);
+
+    let bump_seed = [bump];
+    let signer_seeds = [seeds[0], seeds[1], seeds[2], seeds[3], &bump_seed];
+    let space = 8 + 32 + 8 + 32 + 32 + 1;
+    let rent = Rent::get()?.minimum_balance(space);
+    let accounts = [payer.clone(), marker.clone(), system_program.clone()];
+    // Someone may have pre-funded the address to block the NFT, so top up instead of create
+    if marker.lamports() == 0 {
+        invoke_signed(
+            &system_instruction::create_account(payer.key, marker.key, rent, space as u64, &crate::ID),
+            &accounts,
+            &[&signer_seeds],
+        )?;
+    } else {
+        let top_up = rent.saturating_sub(marker.lamports());
+        if top_up > 0 {
+            invoke(&system_instruction::transfer(payer.key, marker.key, top_up), &accounts)?;
+        }
+        invoke_signed(&system_instruction::allocate(marker.key, space as u64), &accounts, &[&signer_seeds])?;
+        invoke_signed(&system_instruction::assign(marker.key, &crate::ID), &accounts, &[&signer_seeds])?;
+    }
+
+    state.bump = bump;
+    state.try_serialize(&mut &mut marker.try_borrow_mut_data()?[..])?;
+    Ok(())
 }
 
 fn verify_zk_proof(zk_proof: Vec<u8>, public_input: String, verifying_key: &[u8]) -> Result<()> {
@@ -284,13 +1379,219 @@ fn verify_zk_proof(zk_proof: Vec<u8>, public_input: String, verifying_key: &[u8]
 }
 
 impl Election {
-    pub fn reset(&mut self) {
-        self.vote_active = false;
-        self.current = 0;
-        self.number_of_votes = 0;
-        self.proposal_voting.clear();
-        self.value.clear();
-        self.additional_value.clear();
-        self.voters.clear();
+    // A wallet counts once toward quorum, however many NFTs it votes with
+    pub fn record_vote(&mut self, voter: Pubkey, vote: bool, weight: u64) {
+        if vote {
+            self.current += weight as i64;
+            self.votes_for += weight;
+        } else {
+            self.current -= weight as i64;
+            self.votes_against += weight;
+        }
+        if !self.voters.contains(&voter) {
+            self.number_of_votes += 1;
+            self.voters.push(voter);
+        }
+    }
+
+    // Shared by every executor of a proposal's action
+    pub fn start_execution(&mut self) -> Result<()> {
+        require!(self.state != ProposalState::Defeated, CustomError::ProposalNotPassed);
+        require!(self.state == ProposalState::Succeeded, CustomError::InvalidProposalState);
+        require!(!self.executed, CustomError::ActionAlreadyExecuted);
+        require!(Clock::get()?.unix_timestamp >= self.executable_at, CustomError::TimelockActive);
+        self.executed = true;
+        Ok(())
+    }
+
+    // Nothing is left to happen to the proposal, so its account can be closed
+    pub fn is_settled(&self) -> bool {
+        match self.state {
+            ProposalState::Defeated | ProposalState::Cancelled | ProposalState::Vetoed => true,
+            ProposalState::Succeeded => self.executed,
+            ProposalState::Voting => false,
+        }
+    }
+}
+
+impl ProposalResult {
+    // Archives the election as it stands now
+    pub fn record(&mut self, election: &Account<Election>, clock: &Clock, bump: u8) {
+        self.election = election.key();
+        self.dao = election.dao;
+        self.id = election.id;
+        self.creator = election.creator;
+        self.state = election.state;
+        self.votes_for = election.votes_for;
+        self.votes_against = election.votes_against;
+        self.current = election.current;
+        self.number_of_votes = election.number_of_votes;
+        self.vk_version = election.vk_version;
+        self.executable_at = election.executable_at;
+        self.finalized_slot = clock.slot;
+        self.finalized_at = clock.unix_timestamp;
+        self.executed = election.executed;
+        self.bump = bump;
+    }
+}
+
+impl TreasurySpending {
+    /// Counts `amount` against the DAO's caps and returns the total spent this epoch.
+    pub fn charge(&mut self, config: &DaoConfig, amount: u64) -> Result<u64> {
+        // The config may have changed since the proposal was created
+        require!(amount <= config.max_transfer_per_proposal, CustomError::SpendingCapExceeded);
+        let epoch = Clock::get()?.epoch;
+        if self.epoch != epoch {
+            self.epoch = epoch;
+            self.spent = 0;
+        }
+        let spent = self.spent.checked_add(amount).ok_or(CustomError::SpendingCapExceeded)?;
+        require!(spent <= config.max_transfer_per_epoch, CustomError::SpendingCapExceeded);
+        self.spent = spent;
+        Ok(spent)
+    }
+}
+
+impl Stream {
+    pub fn vested(&self, now: i64) -> u64 {
+        if now >= self.end {
+            return self.total;
+        }
+        if now <= self.start {
+            return 0;
+        }
+        (self.total as u128 * (now - self.start) as u128 / (self.end - self.start) as u128) as u64
+    }
+}
+
+// Moves the proposer's deposit off the election account
+fn settle_deposit<'info>(election: &mut Account<'info, Election>, to: &AccountInfo<'info>, refunded: bool) -> Result<()> {
+    let amount = election.deposit;
+    if amount == 0 {
+        return Ok(());
+    }
+    election.deposit = 0;
+    election.sub_lamports(amount)?;
+    to.add_lamports(amount)?;
+
+    emit!(DepositSettled {
+        election: election.key(),
+        amount,
+        refunded,
+    });
+    Ok(())
+}
+
+fn check_treasury_accounts(
+    treasury: &AccountInfo,
+    spending: &TreasurySpending,
+    treasury_token_account: &TokenAccount,
+    mint: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    require_keys_eq!(spending.mint, mint, CustomError::InvalidTreasuryAccounts);
+    require_keys_eq!(treasury_token_account.owner, treasury.key(), CustomError::InvalidTreasuryAccounts);
+    require_keys_eq!(treasury_token_account.mint, mint, CustomError::InvalidTreasuryAccounts);
+    // Funds owed to open streams can't be paid out again
+    require!(
+        treasury_token_account.amount.saturating_sub(spending.reserved) >= amount,
+        CustomError::InsufficientBalance
+    );
+    Ok(())
+}
+
+impl DaoConfig {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.voting_period >= 0 && self.execution_delay >= 0, CustomError::InvalidDaoConfig);
+        // A slashed deposit may be the first lamports the treasury PDA receives
+        require!(
+            self.proposal_deposit == 0 || self.proposal_deposit >= Rent::get()?.minimum_balance(0),
+            CustomError::InvalidDaoConfig
+        );
+        require!(self.weighted_mints.len() <= MAX_WEIGHTED_MINTS, CustomError::InvalidDaoConfig);
+        for (i, weighted_mint) in self.weighted_mints.iter().enumerate() {
+            require!(weighted_mint.weight > 0, CustomError::InvalidDaoConfig);
+            require!(
+                self.weighted_mints[..i].iter().all(|other| other.mint != weighted_mint.mint),
+                CustomError::InvalidDaoConfig
+            );
+        }
+        if let VotingPower::NftCollection { collection } = self.voting_power {
+            require_keys_neq!(collection, Pubkey::default(), CustomError::InvalidDaoConfig);
+        }
+        Ok(())
+    }
+}
+
+impl From<ProposalInstruction> for Instruction {
+    fn from(instruction: ProposalInstruction) -> Self {
+        Instruction {
+            program_id: instruction.program_id,
+            accounts: instruction
+                .accounts
+                .into_iter()
+                .map(|meta| AccountMeta {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: instruction.data,
+        }
+    }
+}
+
+impl ProposalInstruction {
+    pub fn validate(&self, governance: &Pubkey) -> Result<()> {
+        require!(self.program_id != crate::ID, CustomError::InvalidAction);
+        require!(self.accounts.len() <= MAX_INSTRUCTION_ACCOUNTS, CustomError::InvalidAction);
+        require!(self.data.len() <= MAX_INSTRUCTION_DATA_LEN, CustomError::InvalidAction);
+        // The program can only sign for the governance PDA
+        require!(
+            self.accounts.iter().all(|meta| !meta.is_signer || meta.pubkey == *governance),
+            CustomError::InvalidAction
+        );
+        Ok(())
+    }
+}
+
+impl ProposalAction {
+    pub fn validate(&self, dao: &Pubkey, config: &DaoConfig) -> Result<()> {
+        match self {
+            ProposalAction::SetSymbol { symbol, .. } => {
+                require!(!symbol.is_empty() && symbol.len() <= MAX_SYMBOL_LEN, CustomError::InvalidAction);
+            }
+            ProposalAction::SetName { name, .. } => {
+                require!(!name.is_empty() && name.len() <= MAX_NAME_LEN, CustomError::InvalidAction);
+            }
+            ProposalAction::SetDaoConfig { config } => config.validate()?,
+            ProposalAction::CustomInstruction { instructions } => {
+                require!(
+                    !instructions.is_empty() && instructions.len() <= MAX_PROPOSAL_INSTRUCTIONS,
+                    CustomError::InvalidAction
+                );
+                let (governance, _) = Pubkey::find_program_address(&[GOVERNANCE_SEED.as_bytes(), dao.as_ref()], &crate::ID);
+                for instruction in instructions {
+                    instruction.validate(&governance)?;
+                }
+            }
+            ProposalAction::TransferFromTreasury { amount, .. } => {
+                require!(*amount > 0, CustomError::InvalidAmount);
+                require!(*amount <= config.max_transfer_per_proposal, CustomError::SpendingCapExceeded);
+            }
+            ProposalAction::StreamFromTreasury { amount, duration, .. } => {
+                require!(*amount > 0, CustomError::InvalidAmount);
+                require!(*amount <= config.max_transfer_per_proposal, CustomError::SpendingCapExceeded);
+                require!(*duration > 0, CustomError::InvalidAction);
+            }
+            ProposalAction::MintTo { amount, .. } => {
+                require!(*amount > 0, CustomError::InvalidAmount);
+            }
+            ProposalAction::CancelStream { .. }
+            | ProposalAction::SetMintAuthority { .. }
+            | ProposalAction::FreezeAccount { .. }
+            | ProposalAction::ThawAccount { .. } => {}
+        }
+        Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::get_return_data;
+
+use crate::errors::CustomError;
+use crate::state::{ProposalResult, ProposalState};
+
+/// Reads `result` through the `view_result` instruction and decodes the returned record.
+pub fn view_result<'info>(dao_voting_program: AccountInfo<'info>, result: AccountInfo<'info>) -> Result<ProposalResult> {
+    require_keys_eq!(dao_voting_program.key(), crate::ID, ErrorCode::InvalidProgramId);
+    let ctx = CpiContext::new(dao_voting_program, crate::cpi::accounts::ViewResult { result });
+    crate::cpi::view_result(ctx)?;
+
+    // `cpi::Return::get` panics on missing data and doesn't check which program set it
+    let (program_id, data) = get_return_data().ok_or(CustomError::InvalidReturnData)?;
+    require_keys_eq!(program_id, crate::ID, CustomError::InvalidReturnData);
+    ProposalResult::try_from_slice(&data).map_err(|_| error!(CustomError::InvalidReturnData))
+}
+
+/// Like [`view_result`], but fails unless the proposal succeeded and the guardian hasn't vetoed it since.
+pub fn require_passed<'info>(dao_voting_program: AccountInfo<'info>, result: AccountInfo<'info>) -> Result<ProposalResult> {
+    let result = view_result(dao_voting_program, result)?;
+    require!(result.state == ProposalState::Succeeded, CustomError::ProposalNotPassed);
+    Ok(result)
+}
@@ -1,31 +1,169 @@
 use anchor_lang::prelude::*;
 
+#[account]
+pub struct Dao {
+    pub token: Pubkey, // Governing token mint
+    pub config: DaoConfig,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DaoConfig {
+    pub min_votes: u64,
+    pub voting_period: i64, // Seconds a proposal stays open for voting
+    pub execution_delay: i64, // Seconds between a proposal succeeding and becoming executable
+    pub guardian: Pubkey, // May veto active proposals, or succeeded ones during the execution delay
+    pub max_transfer_per_proposal: u64, // Treasury caps, in base units of each mint
+    pub max_transfer_per_epoch: u64,
+    pub proposal_deposit: u64, // Lamports bonded by the proposer, slashed to the treasury without quorum
+    pub voting_power: VotingPower,
+    pub weighted_mints: Vec<WeightedMint>, // Counted by `vote` on top of the governing mint, at most `MAX_WEIGHTED_MINTS`
+}
+
+// A mint whose balance adds `weight` votes per base unit
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeightedMint {
+    pub mint: Pubkey,
+    pub weight: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VotingPower {
+    #[default]
+    Token, // Balance of the governing mint, through `vote`
+    NftCollection { collection: Pubkey }, // One vote per Token-2022 NFT in the collection mint's group, through `vote_nft`
+}
+
+// Tracks how much of one mint the treasury has paid out in the current epoch
+#[account]
+pub struct TreasurySpending {
+    pub dao: Pubkey,
+    pub mint: Pubkey,
+    pub epoch: u64,
+    pub spent: u64,
+    pub reserved: u64, // Owed to open streams, not available for other payouts
+    pub bump: u8,
+}
+
+// Treasury grant paid out linearly between `start` and `end`
+#[account]
+pub struct Stream {
+    pub dao: Pubkey,
+    pub mint: Pubkey,
+    pub recipient: Pubkey, // Token account receiving the claims
+    pub total: u64,
+    pub claimed: u64,
+    pub start: i64,
+    pub end: i64,
+    pub cancelled: bool,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProposalAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProposalInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<ProposalAccountMeta>,
+    pub data: Vec<u8>,
+}
+
+// Mint authorities the DAO can hand over or revoke
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MintAuthorityType {
+    MintTokens,
+    FreezeAccount,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ProposalAction {
+    // Update the metadata extension of a Token-2022 mint whose update authority is the governance PDA
+    SetSymbol { mint: Pubkey, symbol: String },
+    SetName { mint: Pubkey, name: String },
+    TransferFromTreasury { mint: Pubkey, amount: u64, recipient: Pubkey },
+    StreamFromTreasury { mint: Pubkey, amount: u64, recipient: Pubkey, duration: i64 }, // Vests linearly from execution
+    CancelStream { stream: Pubkey }, // Executed by `cancel_stream`
+    // The mint actions below need the governance PDA to hold the mint's authority
+    MintTo { mint: Pubkey, amount: u64, recipient: Pubkey },
+    SetMintAuthority { mint: Pubkey, authority_type: MintAuthorityType, new_authority: Option<Pubkey> }, // `None` revokes it
+    FreezeAccount { mint: Pubkey, account: Pubkey },
+    ThawAccount { mint: Pubkey, account: Pubkey },
+    SetDaoConfig { config: DaoConfig },
+    CustomInstruction { instructions: Vec<ProposalInstruction> }, // Invoked by `execute_proposal`
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProposalState {
+    Voting,
+    Succeeded,
+    Defeated,
+    Cancelled,
+    Vetoed,
+}
+
 #[account]
 pub struct Election {
     pub id: u64, // Unique identifier for the proposal
+    pub dao: Pubkey,
     pub token: Pubkey,
-    pub proposal_voting: String,
-    pub value: String,
-    pub additional_value: String,
+    pub action: ProposalAction,
+    pub executed: bool, // Set once the action has been applied
+    pub state: ProposalState,
+    pub executable_at: i64, // Earliest execution time once succeeded
+    pub vk_version: u32, // Verifying key version the proposal was opened under
     pub current: i64, // Modified to i64 to accommodate negative votes
+    pub votes_for: u64,
+    pub votes_against: u64,
     pub number_of_votes: u64,
     pub vote_active: bool,
     pub time: i64,
     pub min_votes: u64,
+    pub deposit: u64, // Proposer's bond, held on this account until finalization
+    pub voting_power: VotingPower,
+    pub weighted_mints: Vec<WeightedMint>,
     pub count: u64,
     pub creator: Pubkey, // The address of the proposal creator
     pub voters: Vec<Pubkey>, // List of voters
 }
 
+// Written when the proposal is finalized, cancelled or vetoed, and kept in step with a later veto or
+// execution, so it outlives `close_election`
 #[account]
-pub struct ChangableTokenAccount {
-    pub name: String,
-    pub symbol: String,
-    pub balance: u64,
+pub struct ProposalResult {
+    pub election: Pubkey,
+    pub dao: Pubkey,
+    pub id: u64,
+    pub creator: Pubkey,
+    pub state: ProposalState, // Never `Voting`
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub current: i64,
+    pub number_of_votes: u64, // Turnout
+    pub vk_version: u32,
+    pub executable_at: i64,
+    pub finalized_slot: u64,
+    pub finalized_at: i64,
+    pub executed: bool,
+    pub bump: u8,
+}
+
+// Created the first time an NFT votes on a proposal, so it can't vote again from another wallet
+#[account]
+pub struct NftVoteMarker {
+    pub election: Pubkey,
+    pub id: u64,
+    pub mint: Pubkey,
+    pub voter: Pubkey,
+    pub bump: u8,
 }
 
 #[account]
 pub struct User {
     pub pubkey: Pubkey,
     pub reward_points: u64,
-}
\ No newline at end of file
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::*;
+use dao_voting::errors::CustomError;
+use dao_voting::state::*;
+use solana_sdk::signature::{Keypair, Signer};
+
+async fn setup(guardian: &Keypair) -> TestDao {
+    let mut dao = start(program_test(), 100).await;
+    dao.fund(guardian.pubkey());
+    let ix = dao.initialize_dao_ix(DaoConfig {
+        min_votes: 1,
+        voting_period: 3600,
+        execution_delay: 0,
+        guardian: guardian.pubkey(),
+        ..DaoConfig::default()
+    });
+    let action = ProposalAction::SetName { mint: dao.mint, name: "New Name".to_string() };
+    let ixs = [ix, dao.new_polling_ix(action)];
+    dao.process(&ixs, &[]).await.unwrap();
+    dao
+}
+
+#[tokio::test]
+async fn creator_cancels_proposal_without_votes() {
+    let mut dao = setup(&Keypair::new()).await;
+
+    let ix = dao.cancel_proposal_ix(dao.payer()).await;
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert_eq!(election.state, ProposalState::Cancelled);
+    assert!(!election.vote_active);
+    let result: ProposalResult = dao.account(result_address(&dao.payer(), election.id)).await;
+    assert_eq!(result.state, ProposalState::Cancelled);
+    assert_eq!(result.number_of_votes, 0);
+}
+
+#[tokio::test]
+async fn creator_cannot_cancel_after_votes() {
+    let mut dao = setup(&Keypair::new()).await;
+    let ix = dao.vote_ix(true);
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let ix = dao.cancel_proposal_ix(dao.payer()).await;
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::VotesAlreadyCast);
+}
+
+#[tokio::test]
+async fn only_creator_can_cancel() {
+    let mut dao = setup(&Keypair::new()).await;
+
+    let other = Keypair::new();
+    dao.fund(other.pubkey());
+    let ix = dao.cancel_proposal_ix(other.pubkey()).await;
+    assert_custom_error(dao.process(&[ix], &[&other]).await, CustomError::Unauthorized);
+}
+
+#[tokio::test]
+async fn guardian_vetoes_active_proposal() {
+    let guardian = Keypair::new();
+    let mut dao = setup(&guardian).await;
+
+    let ix = dao.veto_proposal_ix(guardian.pubkey()).await;
+    dao.process(&[ix], &[&guardian]).await.unwrap();
+
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert_eq!(election.state, ProposalState::Vetoed);
+    let result: ProposalResult = dao.account(result_address(&dao.payer(), election.id)).await;
+    assert_eq!(result.state, ProposalState::Vetoed);
+
+    let ix = dao.vote_ix(true);
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::VoteInactive);
+}
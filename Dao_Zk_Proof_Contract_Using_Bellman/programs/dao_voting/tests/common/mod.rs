@@ -0,0 +1,1032 @@
+#![allow(dead_code)]
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, Event, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    metadata_pointer, transfer_hook, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+use base64::Engine;
+use bellman::groth16::{create_random_proof, generate_random_parameters};
+use bellman::{Circuit, ConstraintSystem, SynthesisError};
+use bls12_381::{Bls12, Scalar};
+use dao_voting::constants::*;
+use dao_voting::errors::CustomError;
+use dao_voting::state::*;
+use dao_voting::VerifyingKey;
+use rand::rngs::OsRng;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_option::COption;
+use solana_program::program_pack::Pack;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use solana_program_test::{
+    processor, BanksClientError, BanksTransactionResultWithMetadata, ProgramTest, ProgramTestContext,
+};
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::{system_instruction, system_program};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use spl_token_metadata_interface::state::TokenMetadata;
+use ::spl_token_2022::extension::{group_member_pointer, group_pointer};
+
+// Anchor ties the account slice to the `'info` lifetime, which `processor!` can't express
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    dao_voting::entry(program_id, accounts, data)
+}
+
+// Natively, `sol_log_data` only prints to stdout, so `emit!` never reaches the transaction logs.
+// Wraps the program-test stubs and routes it through `sol_log` as a "Program data: " line instead.
+struct LogDataStubs(Box<dyn SyscallStubs>);
+
+impl SyscallStubs for LogDataStubs {
+    fn sol_log(&self, message: &str) {
+        self.0.sol_log(message)
+    }
+    fn sol_log_compute_units(&self) {
+        self.0.sol_log_compute_units()
+    }
+    fn sol_remaining_compute_units(&self) -> u64 {
+        self.0.sol_remaining_compute_units()
+    }
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        self.0.sol_invoke_signed(instruction, account_infos, signers_seeds)
+    }
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_clock_sysvar(var_addr)
+    }
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_schedule_sysvar(var_addr)
+    }
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_fees_sysvar(var_addr)
+    }
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_rent_sysvar(var_addr)
+    }
+    fn sol_get_epoch_rewards_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_rewards_sysvar(var_addr)
+    }
+    fn sol_get_last_restart_slot(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_last_restart_slot(var_addr)
+    }
+    unsafe fn sol_memcpy(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.0.sol_memcpy(dst, src, n)
+    }
+    unsafe fn sol_memmove(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.0.sol_memmove(dst, src, n)
+    }
+    unsafe fn sol_memcmp(&self, s1: *const u8, s2: *const u8, n: usize, result: *mut i32) {
+        self.0.sol_memcmp(s1, s2, n, result)
+    }
+    unsafe fn sol_memset(&self, s: *mut u8, c: u8, n: usize) {
+        self.0.sol_memset(s, c, n)
+    }
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        self.0.sol_get_return_data()
+    }
+    fn sol_set_return_data(&self, data: &[u8]) {
+        self.0.sol_set_return_data(data)
+    }
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        let fields: Vec<String> = fields.iter().map(|field| base64::engine::general_purpose::STANDARD.encode(field)).collect();
+        self.0.sol_log(&format!("Program data: {}", fields.join(" ")))
+    }
+    fn sol_get_processed_sibling_instruction(&self, index: usize) -> Option<Instruction> {
+        self.0.sol_get_processed_sibling_instruction(index)
+    }
+    fn sol_get_stack_height(&self) -> u64 {
+        self.0.sol_get_stack_height()
+    }
+}
+
+// Only the first caller sees the program-test stubs; every test shares the wrapper afterwards
+fn install_log_data_stubs() {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    INSTALL.call_once(|| {
+        let stubs = set_syscall_stubs(Box::new(NoStubs));
+        set_syscall_stubs(Box::new(LogDataStubs(stubs)));
+    });
+}
+
+struct NoStubs;
+impl SyscallStubs for NoStubs {}
+
+// Proves knowledge of `root` such that `root * root == square`, with `square` public
+struct SquareCircuit {
+    root: Option<Scalar>,
+}
+
+impl Circuit<Scalar> for SquareCircuit {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let root = cs.alloc(|| "root", || self.root.ok_or(SynthesisError::AssignmentMissing))?;
+        let square = cs.alloc_input(
+            || "square",
+            || self.root.map(|root| root.square()).ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        cs.enforce(|| "root * root = square", |lc| lc + root, |lc| lc + root, |lc| lc + square);
+        Ok(())
+    }
+}
+
+pub struct ZkFixture {
+    pub verifying_key: Vec<u8>,
+    pub proof: Vec<u8>,
+    pub public_input: String,
+}
+
+impl ZkFixture {
+    pub fn new() -> Self {
+        let mut rng = OsRng;
+        let params =
+            generate_random_parameters::<Bls12, _, _>(SquareCircuit { root: None }, &mut rng).unwrap();
+        let proof = create_random_proof(SquareCircuit { root: Some(Scalar::from(2)) }, &params, &mut rng).unwrap();
+
+        let mut verifying_key = vec![];
+        params.vk.write(&mut verifying_key).unwrap();
+        let mut proof_bytes = vec![];
+        proof.write(&mut proof_bytes).unwrap();
+
+        let mut square = [0u8; 32];
+        square[0] = 4;
+        ZkFixture {
+            verifying_key,
+            proof: proof_bytes,
+            public_input: hex::encode(square),
+        }
+    }
+}
+
+pub struct TestDao {
+    pub context: ProgramTestContext,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub verifying_key: Pubkey,
+    pub zk: ZkFixture,
+}
+
+pub fn program_test() -> ProgramTest {
+    ProgramTest::new("dao_voting", dao_voting::ID, processor!(process_instruction))
+}
+
+/// `program_test` with Token-2022 running natively, as the bundled build leaves out token groups.
+pub fn program_test_with_token_groups() -> ProgramTest {
+    let mut program_test = program_test();
+    program_test.add_program(
+        "spl_token_2022",
+        ::spl_token_2022::id(),
+        processor!(::spl_token_2022::processor::Processor::process),
+    );
+    program_test
+}
+
+pub fn token_account_data(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::id(),
+        ..Account::default()
+    }
+}
+
+pub fn program_account<T: AccountSerialize>(account: &T) -> Account {
+    let mut data = vec![];
+    account.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: dao_voting::ID,
+        ..Account::default()
+    }
+}
+
+pub fn mint_data(mint_authority: Pubkey, freeze_authority: Option<Pubkey>, supply: u64) -> Account {
+    let mut data = vec![0u8; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::Some(mint_authority),
+        supply,
+        decimals: 0,
+        is_initialized: true,
+        freeze_authority: freeze_authority.into(),
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::id(),
+        ..Account::default()
+    }
+}
+
+/// Starts `program_test` with a governing token account and verifying key for the payer.
+pub async fn start(program_test: ProgramTest, balance: u64) -> TestDao {
+    let mut context = program_test.start_with_context().await;
+    install_log_data_stubs();
+
+    let mint = Pubkey::new_unique();
+    context.set_account(&mint, &mint_data(context.payer.pubkey(), None, balance).into());
+    let token_account = Pubkey::new_unique();
+    context.set_account(&token_account, &token_account_data(mint, context.payer.pubkey(), balance).into());
+    TestDao::new(context, mint, token_account)
+}
+
+/// Like `start`, but the governing mint is a Token-2022 mint initialized with `extensions`.
+pub async fn start_token_2022(program_test: ProgramTest, balance: u64, extensions: &[ExtensionType]) -> TestDao {
+    let mut context = program_test.start_with_context().await;
+    install_log_data_stubs();
+
+    let (mint, token_account) = (Keypair::new(), Keypair::new());
+    let payer = context.payer.pubkey();
+    let token_2022 = spl_token_2022::id();
+    // Immutable owner, like an associated token account, which non-transferable mints require
+    let mut account_extensions = ExtensionType::get_required_init_account_extensions(extensions);
+    account_extensions.push(ExtensionType::ImmutableOwner);
+    let account_len =
+        ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(&account_extensions).unwrap();
+
+    let mut ixs = token_2022_mint_ixs(&payer, &mint.pubkey(), extensions);
+    ixs.extend([
+        system_instruction::create_account(&payer, &token_account.pubkey(), 1_000_000_000, account_len as u64, &token_2022),
+        spl_token_2022::instruction::initialize_immutable_owner(&token_2022, &token_account.pubkey()).unwrap(),
+        spl_token_2022::instruction::initialize_account3(&token_2022, &token_account.pubkey(), &mint.pubkey(), &payer)
+            .unwrap(),
+        spl_token_2022::instruction::mint_to(&token_2022, &mint.pubkey(), &token_account.pubkey(), &payer, &[], balance)
+            .unwrap(),
+    ]);
+    let blockhash = context.last_blockhash;
+    let transaction =
+        Transaction::new_signed_with_payer(&ixs, Some(&payer), &[&context.payer, &mint, &token_account], blockhash);
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    TestDao::new(context, mint.pubkey(), token_account.pubkey())
+}
+
+// Creates `mint` as a Token-2022 mint of `mint_authority`, initialized with `extensions`
+fn token_2022_mint_ixs(mint_authority: &Pubkey, mint: &Pubkey, extensions: &[ExtensionType]) -> Vec<Instruction> {
+    let token_2022 = spl_token_2022::id();
+    let mint_len = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(extensions).unwrap();
+    let mut ixs = vec![system_instruction::create_account(mint_authority, mint, 1_000_000_000, mint_len as u64, &token_2022)];
+    for extension in extensions {
+        ixs.push(match extension {
+            ExtensionType::NonTransferable => {
+                spl_token_2022::instruction::initialize_non_transferable_mint(&token_2022, mint).unwrap()
+            }
+            ExtensionType::TransferHook => {
+                transfer_hook::instruction::initialize(&token_2022, mint, Some(*mint_authority), Some(Pubkey::new_unique()))
+                    .unwrap()
+            }
+            other => panic!("unsupported extension {other:?}"),
+        });
+    }
+    ixs.push(spl_token_2022::instruction::initialize_mint2(&token_2022, mint, mint_authority, None, 0).unwrap());
+    ixs
+}
+
+/// The weighted mints `initialize_dao` and a `SetDaoConfig` execution check, in config order.
+pub fn weighted_mint_metas(config: &DaoConfig) -> Vec<AccountMeta> {
+    config
+        .weighted_mints
+        .iter()
+        .map(|weighted_mint| AccountMeta::new_readonly(weighted_mint.mint, false))
+        .collect()
+}
+
+pub fn dao_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[DAO_SEED.as_bytes(), mint.as_ref()], &dao_voting::ID).0
+}
+
+pub fn governance_address(dao: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[GOVERNANCE_SEED.as_bytes(), dao.as_ref()], &dao_voting::ID).0
+}
+
+pub fn verifying_key_address(dao: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[VERIFYING_KEY_SEED.as_bytes(), dao.as_ref()], &dao_voting::ID).0
+}
+
+pub fn election_address(creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ELECTION_SEED.as_bytes(), creator.as_ref()], &dao_voting::ID).0
+}
+
+pub fn result_address(creator: &Pubkey, id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[RESULT_SEED.as_bytes(), creator.as_ref(), &id.to_le_bytes()],
+        &dao_voting::ID,
+    )
+    .0
+}
+
+pub fn treasury_address(dao: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[TREASURY_SEED.as_bytes(), dao.as_ref()], &dao_voting::ID).0
+}
+
+pub fn spending_address(dao: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[SPENDING_SEED.as_bytes(), dao.as_ref(), mint.as_ref()], &dao_voting::ID).0
+}
+
+pub fn stream_address(creator: &Pubkey, id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[STREAM_SEED.as_bytes(), creator.as_ref(), &id.to_le_bytes()],
+        &dao_voting::ID,
+    )
+    .0
+}
+
+pub fn nft_vote_marker_address(creator: &Pubkey, id: u64, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[NFT_VOTE_SEED.as_bytes(), creator.as_ref(), &id.to_le_bytes(), mint.as_ref()],
+        &dao_voting::ID,
+    )
+    .0
+}
+
+pub fn user_address(voter: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[USER_SEED.as_bytes(), voter.as_ref()], &dao_voting::ID).0
+}
+
+impl TestDao {
+    // Installs the verifying key for the DAO governing `mint`
+    fn new(mut context: ProgramTestContext, mint: Pubkey, token_account: Pubkey) -> Self {
+        let zk = ZkFixture::new();
+        let verifying_key = verifying_key_address(&dao_address(&mint));
+        let mut account = program_account(&VerifyingKey { key: zk.verifying_key.clone(), version: 1 });
+        // Sized like `set_verifying_key` allocates it, so the key can be replaced in place
+        account.data.resize(8 + 4 + MAX_VERIFYING_KEY_LEN + 4, 0);
+        context.set_account(&verifying_key, &account.into());
+
+        TestDao {
+            context,
+            mint,
+            token_account,
+            verifying_key,
+            zk,
+        }
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    pub async fn process(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<(), BanksClientError> {
+        let blockhash = self.context.last_blockhash;
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction =
+            Transaction::new_signed_with_payer(instructions, Some(&self.context.payer.pubkey()), &all_signers, blockhash);
+        self.context.banks_client.process_transaction(transaction).await
+    }
+
+    /// Processes `instructions` and returns the program log lines.
+    pub async fn process_with_logs(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Vec<String> {
+        self.process_with_metadata(instructions, signers).await.metadata.unwrap().log_messages
+    }
+
+    /// Processes `instructions` and returns the data set through `set_return_data`.
+    pub async fn process_with_return_data(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Vec<u8> {
+        let result = self.process_with_metadata(instructions, signers).await;
+        let return_data = result.metadata.unwrap().return_data.unwrap();
+        assert_eq!(return_data.program_id, dao_voting::ID);
+        return_data.data
+    }
+
+    async fn process_with_metadata(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> BanksTransactionResultWithMetadata {
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            self.context.last_blockhash,
+        );
+        let result = self.context.banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+        result.result.clone().unwrap();
+        result
+    }
+
+    pub async fn account<T: AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self.context.banks_client.get_account(address).await.unwrap().unwrap();
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub fn initialize_dao_ix(&self, config: DaoConfig) -> Instruction {
+        self.initialize_dao_as_ix(self.context.payer.pubkey(), config)
+    }
+
+    /// Like `initialize_dao_ix`, signed and paid for by `authority` instead of the mint authority.
+    pub fn initialize_dao_as_ix(&self, authority: Pubkey, config: DaoConfig) -> Instruction {
+        let mut accounts = dao_voting::accounts::InitializeDao {
+            dao: dao_address(&self.mint),
+            mint: self.mint,
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(weighted_mint_metas(&config));
+        Instruction {
+            program_id: dao_voting::ID,
+            accounts,
+            data: dao_voting::instruction::InitializeDao { token: self.mint, config }.data(),
+        }
+    }
+
+    pub fn set_verifying_key_ix(&self, authority: Pubkey, key: Vec<u8>) -> Instruction {
+        let dao = dao_address(&self.mint);
+        Instruction {
+            program_id: dao_voting::ID,
+            accounts: dao_voting::accounts::SetVerifyingKey {
+                verifying_key: verifying_key_address(&dao),
+                dao,
+                authority,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_voting::instruction::SetVerifyingKey { key }.data(),
+        }
+    }
+
+    pub fn new_polling_ix(&self, action: ProposalAction) -> Instruction {
+        self.new_polling_with_ix(action, self.token_account, None)
+    }
+
+    /// Opens a proposal in an NFT DAO, holding `nft_mint` in `token_account`.
+    pub fn new_polling_nft_ix(&self, action: ProposalAction, token_account: Pubkey, nft_mint: Pubkey) -> Instruction {
+        self.new_polling_with_ix(action, token_account, Some(nft_mint))
+    }
+
+    fn new_polling_with_ix(&self, action: ProposalAction, token_account: Pubkey, nft_mint: Option<Pubkey>) -> Instruction {
+        let payer = self.context.payer.pubkey();
+        Instruction {
+            program_id: dao_voting::ID,
+            accounts: dao_voting::accounts::NewPolling {
+                election: election_address(&payer),
+                dao: dao_address(&self.mint),
+                authority: payer,
+                token_account,
+                nft_mint,
+                verifying_key: self.verifying_key,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_voting::instruction::NewPolling { action }.data(),
+        }
+    }
+
+    pub fn vote_ix(&self, vote: bool) -> Instruction {
+        let payer = self.context.payer.pubkey();
+        Instruction {
+            program_id: dao_voting::ID,
+            accounts: dao_voting::accounts::Vote {
+                election: election_address(&payer),
+                authority: payer,
+                token_account: self.token_account,
+                user: user_address(&payer),
+                verifying_key: self.verifying_key,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_voting::instruction::Vote {
+                vote,
+                zk_proof: self.zk.proof.clone(),
+                public_input: self.zk.public_input.clone(),
+            }
+            .data(),
+        }
+    }
+
+    /// Like `vote_ix`, also counting the weighted mints held in `token_accounts`.
+    pub fn vote_weighted_ix(&self, vote: bool, token_accounts: &[Pubkey]) -> Instruction {
+        let mut ix = self.vote_ix(vote);
+        ix.accounts
+            .extend(token_accounts.iter().map(|token_account| AccountMeta::new_readonly(*token_account, false)));
+        ix
+    }
+
+    /// Votes on the payer's election with the `(token account, mint)` NFTs held by `voter`.
+    pub async fn vote_nft_ix(&mut self, voter: Pubkey, vote: bool, nfts: &[(Pubkey, Pubkey)]) -> Instruction {
+        let payer = self.payer();
+        let id = self.election_id().await;
+        let mut accounts = dao_voting::accounts::VoteNft {
+            election: election_address(&payer),
+            authority: voter,
+            user: user_address(&voter),
+            verifying_key: self.verifying_key,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        for (token_account, mint) in nfts {
+            accounts.extend([
+                AccountMeta::new_readonly(*token_account, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(nft_vote_marker_address(&payer, id, mint), false),
+            ]);
+        }
+        Instruction {
+            program_id: dao_voting::ID,
+            accounts,
+            data: dao_voting::instruction::VoteNft {
+                vote,
+                zk_proof: self.zk.proof.clone(),
+                public_input: self.zk.public_input.clone(),
+            }
+            .data(),
+        }
+    }
+
+    /// Id of the payer's election, or the id a proposal opened now would get.
+    pub async fn election_id(&mut self) -> u64 {
+        let election = election_address(&self.payer());
+        match self.context.banks_client.get_account(election).await.unwrap() {
+            Some(_) => self.account::<Election>(election).await.id,
+            None => self.now().await as u64,
+        }
+    }
+
+    pub async fn sum_up_ix(&mut self) -> Instruction {
+        let payer = self.context.payer.pubkey();
+        let id = self.election_id().await;
+        Instruction {
+            program_id: dao_voting::ID,
+            accounts: dao_voting::accounts::ToSumUp {
+                election: election_address(&payer),
+                dao: dao_address(&self.mint),
+                result: result_address(&payer, id),
+                creator: payer,
+                treasury: treasury_address(&dao_address(&self.mint)),
+                authority: payer,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_voting::instruction::ToSumUp {}.data(),
+        }
+    }
+
+    pub fn get_results_ix(&self) -> Instruction {
+        Instruction {
+            program_id: dao_voting::ID,
+            accounts: dao_voting::accounts::GetResults { election: election_address(&self.payer()) }
+                .to_account_metas(None),
+            data: dao_voting::instruction::GetResults {}.data(),
+        }
+    }
+
+    pub fn view_result_ix(&self, result: Pubkey) -> Instruction {
+        Instruction {
+            program_id: dao_voting::ID,
+            accounts: dao_voting::accounts::ViewResult { result }.to_account_metas(None),
+            data: dao_voting::instruction::ViewResult {}.data(),
+        }
+    }
+
+    pub fn close_election_ix(&self, creator: Pubkey) -> Instruction {
+        Instruction {
+            program_id: dao_voting::ID,
+            accounts: dao_voting::accounts::CloseElection {
+                election: election_address(&self.payer()),
+                creator,
+            }
+            .to_account_metas(None),
+            data: dao_voting::instruction::CloseElection {}.data(),
+        }
+    }
+
+    pub async fn execute_proposal_ix(&mut self, remaining_accounts: Vec<solana_sdk::instruction::AccountMeta>) -> Instruction {
+        let mut ix = Self::execute_ix(self.execute_accounts().await);
+        ix.accounts.extend(remaining_accounts);
+        ix
+    }
+
+    /// Executes a `TransferFromTreasury` proposal paying `mint` out to the `recipient` token account.
+    pub async fn execute_transfer_ix(&mut self, mint: Pubkey, recipient: Pubkey) -> Instruction {
+        Self::execute_ix(dao_voting::accounts::ExecuteProposal {
+            recipient: Some(recipient),
+            token_program: Some(spl_token::id()),
+            ..self.execute_treasury_accounts(mint).await
+        })
+    }
+
+    /// Executes a `StreamFromTreasury` proposal of `mint`, creating its stream account.
+    pub async fn execute_stream_ix(&mut self, mint: Pubkey) -> Instruction {
+        let stream = stream_address(&self.payer(), self.election_id().await);
+        Self::execute_ix(dao_voting::accounts::ExecuteProposal {
+            stream: Some(stream),
+            ..self.execute_treasury_accounts(mint).await
+        })
+    }
+
+    /// Executes a mint action on `mint`, passing `token_account` as the minted-to or frozen account.
+    pub async fn execute_mint_ix(&mut self, mint: Pubkey, token_account: Option<Pubkey>) -> Instruction {
+        Self::execute_ix(dao_voting::accounts::ExecuteProposal {
+            recipient: token_account,
+            mint: Some(mint),
+            token_program: Some(spl_token::id()),
+            ..self.execute_accounts().await
+        })
+    }
+
+    /// Executes a `SetName` or `SetSymbol` proposal on the Token-2022 `mint`.
+    pub async fn execute_metadata_ix(&mut self, mint: Pubkey) -> Instruction {
+        Self::execute_ix(dao_voting::accounts::ExecuteProposal {
+            metadata_mint: Some(mint),
+            token_2022_program: Some(spl_token_2022::id()),
+            ..self.execute_accounts().await
+        })
+    }
+
+    // Required accounts only; every optional one is left out
+    async fn execute_accounts(&mut self) -> dao_voting::accounts::ExecuteProposal {
+        let payer = self.context.payer.pubkey();
+        let dao = dao_address(&self.mint);
+        let id = self.election_id().await;
+        dao_voting::accounts::ExecuteProposal {
+            election: election_address(&payer),
+            result: result_address(&payer, id),
+            dao,
+            governance: governance_address(&dao),
+            authority: payer,
+            system_program: system_program::ID,
+            treasury: None,
+            spending: None,
+            treasury_token_account: None,
+            recipient: None,
+            mint: None,
+            token_program: None,
+            stream: None,
+            metadata_mint: None,
+            token_2022_program: None,
+        }
+    }
+
+    async fn execute_treasury_accounts(&mut self, mint: Pubkey) -> dao_voting::accounts::ExecuteProposal {
+        let dao = dao_address(&self.mint);
+        let treasury = treasury_address(&dao);
+        dao_voting::accounts::ExecuteProposal {
+            treasury: Some(treasury),
+            spending: Some(spending_address(&dao, &mint)),
+            treasury_token_account: Some(get_associated_token_address(&treasury, &mint)),
+            ..self.execute_accounts().await
+        }
+    }
+
+    fn execute_ix(accounts: dao_voting::accounts::ExecuteProposal) -> Instruction {
+        Instruction {
+            program_id: dao_voting::ID,
+            accounts: accounts.to_account_metas(None),
+            data: dao_voting::instruction::ExecuteProposal {}.data(),
+        }
+    }
+
+    pub fn claim_stream_ix(&self, stream: Pubkey, mint: Pubkey, recipient: Pubkey) -> Instruction {
+        let dao = dao_address(&self.mint);
+        let treasury = treasury_address(&dao);
+        Instruction {
+            program_id: dao_voting::ID,
+            accounts: dao_voting::accounts::ClaimStream {
+                stream,
+                dao,
+                treasury,
+                spending: spending_address(&dao, &mint),
+                treasury_token_account: get_associated_token_address(&treasury, &mint),
+                recipient,
+                token_program: spl_token::id(),
+            }
+            .to_account_metas(None),
+            data: dao_voting::instruction::ClaimStream {}.data(),
+        }
+    }
+
+    pub async fn cancel_stream_ix(&mut self, stream: Pubkey, mint: Pubkey) -> Instruction {
+        let dao = dao_address(&self.mint);
+        let result = result_address(&self.payer(), self.election_id().await);
+        Instruction {
+            program_id: dao_voting::ID,
+            accounts: dao_voting::accounts::CancelStream {
+                election: election_address(&self.payer()),
+                result,
+                dao,
+                stream,
+                spending: spending_address(&dao, &mint),
+                authority: self.payer(),
+            }
+            .to_account_metas(None),
+            data: dao_voting::instruction::CancelStream {}.data(),
+        }
+    }
+
+    pub fn deposit_ix(&self, mint: Pubkey, depositor_token_account: Pubkey, amount: u64) -> Instruction {
+        let payer = self.payer();
+        let dao = dao_address(&self.mint);
+        let treasury = treasury_address(&dao);
+        Instruction {
+            program_id: dao_voting::ID,
+            accounts: dao_voting::accounts::Deposit {
+                dao,
+                treasury,
+                spending: spending_address(&dao, &mint),
+                mint,
+                treasury_token_account: get_associated_token_address(&treasury, &mint),
+                depositor_token_account,
+                depositor: payer,
+                token_program: spl_token::id(),
+                associated_token_program: anchor_spl::associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_voting::instruction::Deposit { amount }.data(),
+        }
+    }
+
+    pub async fn cancel_proposal_ix(&mut self, creator: Pubkey) -> Instruction {
+        let result = result_address(&self.payer(), self.election_id().await);
+        Instruction {
+            program_id: dao_voting::ID,
+            accounts: dao_voting::accounts::CancelProposal {
+                election: election_address(&self.payer()),
+                result,
+                creator,
+                treasury: treasury_address(&dao_address(&self.mint)),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_voting::instruction::CancelProposal {}.data(),
+        }
+    }
+
+    pub async fn veto_proposal_ix(&mut self, guardian: Pubkey) -> Instruction {
+        let result = result_address(&self.payer(), self.election_id().await);
+        Instruction {
+            program_id: dao_voting::ID,
+            accounts: dao_voting::accounts::VetoProposal {
+                election: election_address(&self.payer()),
+                dao: dao_address(&self.mint),
+                result,
+                guardian,
+                treasury: treasury_address(&dao_address(&self.mint)),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dao_voting::instruction::VetoProposal {}.data(),
+        }
+    }
+
+    pub async fn set_clock(&mut self, unix_timestamp: i64) {
+        let mut clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp = unix_timestamp;
+        self.context.set_sysvar(&clock);
+        // Transactions retried after a clock change need a fresh signature
+        self.context.get_new_latest_blockhash().await.unwrap();
+    }
+
+    /// Funds `address` so it can pay rent, like the guardian paying for a vetoed proposal's result.
+    pub fn fund(&mut self, address: Pubkey) {
+        let account = Account { lamports: 1_000_000_000, ..Account::default() };
+        self.context.set_account(&address, &account.into());
+    }
+
+    pub async fn set_epoch(&mut self, epoch: u64) {
+        let mut clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.epoch = epoch;
+        self.context.set_sysvar(&clock);
+        self.context.get_new_latest_blockhash().await.unwrap();
+    }
+
+    /// Creates a mint and returns it with a token account holding `amount` for the payer.
+    pub fn create_mint(&mut self, amount: u64) -> (Pubkey, Pubkey) {
+        let mint = self.create_mint_account(self.payer(), None, amount);
+        let token_account = self.create_token_account(mint, self.payer(), amount);
+        (mint, token_account)
+    }
+
+    /// Creates an empty Token-2022 mint of the payer, initialized with `extensions`.
+    pub async fn create_token_2022_mint(&mut self, extensions: &[ExtensionType]) -> Pubkey {
+        let mint = Keypair::new();
+        let ixs = token_2022_mint_ixs(&self.payer(), &mint.pubkey(), extensions);
+        self.process(&ixs, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    /// Creates an empty mint whose mint and freeze authority is the DAO's governance PDA.
+    pub fn create_governed_mint(&mut self) -> Pubkey {
+        let governance = governance_address(&dao_address(&self.mint));
+        self.create_mint_account(governance, Some(governance), 0)
+    }
+
+    /// Creates a Token-2022 mint carrying `name` and `symbol` in its metadata extension, with the
+    /// governance PDA as update authority.
+    pub async fn create_metadata_mint(&mut self, name: &str, symbol: &str) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.payer();
+        let governance = governance_address(&dao_address(&self.mint));
+        let space =
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[ExtensionType::MetadataPointer])
+                .unwrap();
+        let ixs = [
+            // Funded well past the rent of `space`, since the metadata is appended later
+            system_instruction::create_account(&payer, &mint.pubkey(), 1_000_000_000, space as u64, &spl_token_2022::id()),
+            metadata_pointer::instruction::initialize(
+                &spl_token_2022::id(),
+                &mint.pubkey(),
+                Some(governance),
+                Some(mint.pubkey()),
+            )
+            .unwrap(),
+            spl_token_2022::instruction::initialize_mint2(&spl_token_2022::id(), &mint.pubkey(), &payer, None, 0)
+                .unwrap(),
+            spl_token_metadata_interface::instruction::initialize(
+                &spl_token_2022::id(),
+                &mint.pubkey(),
+                &governance,
+                &mint.pubkey(),
+                &payer,
+                name.to_string(),
+                symbol.to_string(),
+                String::new(),
+            ),
+        ];
+        self.process(&ixs, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    /// Creates a Token-2022 collection mint whose token group lives in the mint itself.
+    pub async fn create_collection(&mut self) -> Pubkey {
+        let collection = Keypair::new();
+        let payer = self.payer();
+        let space =
+            ::spl_token_2022::extension::ExtensionType::try_calculate_account_len::<::spl_token_2022::state::Mint>(&[
+                ::spl_token_2022::extension::ExtensionType::GroupPointer,
+            ])
+            .unwrap();
+        let ixs = [
+            // Funded well past the rent of `space`, since the group is appended later
+            system_instruction::create_account(
+                &payer,
+                &collection.pubkey(),
+                1_000_000_000,
+                space as u64,
+                &spl_token_2022::id(),
+            ),
+            group_pointer::instruction::initialize(
+                &spl_token_2022::id(),
+                &collection.pubkey(),
+                Some(payer),
+                Some(collection.pubkey()),
+            )
+            .unwrap(),
+            spl_token_2022::instruction::initialize_mint2(&spl_token_2022::id(), &collection.pubkey(), &payer, None, 0)
+                .unwrap(),
+            spl_token_group_interface::instruction::initialize_group(
+                &spl_token_2022::id(),
+                &collection.pubkey(),
+                &collection.pubkey(),
+                &payer,
+                Some(payer),
+                100,
+            ),
+        ];
+        self.process(&ixs, &[&collection]).await.unwrap();
+        collection.pubkey()
+    }
+
+    /// Creates an NFT enrolled in `collection`, minted into a new token account of `owner`.
+    pub async fn create_nft(&mut self, collection: Pubkey, owner: Pubkey) -> (Pubkey, Pubkey) {
+        let mint = Keypair::new();
+        let payer = self.payer();
+        let space =
+            ::spl_token_2022::extension::ExtensionType::try_calculate_account_len::<::spl_token_2022::state::Mint>(&[
+                ::spl_token_2022::extension::ExtensionType::GroupMemberPointer,
+            ])
+            .unwrap();
+        let ixs = [
+            system_instruction::create_account(&payer, &mint.pubkey(), 1_000_000_000, space as u64, &spl_token_2022::id()),
+            group_member_pointer::instruction::initialize(
+                &spl_token_2022::id(),
+                &mint.pubkey(),
+                Some(payer),
+                Some(mint.pubkey()),
+            )
+            .unwrap(),
+            spl_token_2022::instruction::initialize_mint2(&spl_token_2022::id(), &mint.pubkey(), &payer, None, 0)
+                .unwrap(),
+            spl_token_group_interface::instruction::initialize_member(
+                &spl_token_2022::id(),
+                &mint.pubkey(),
+                &mint.pubkey(),
+                &payer,
+                &collection,
+                &payer,
+            ),
+        ];
+        self.process(&ixs, &[&mint]).await.unwrap();
+
+        let token_account = self.create_token_2022_account(mint.pubkey(), owner).await;
+        let ix = spl_token_2022::instruction::mint_to(&spl_token_2022::id(), &mint.pubkey(), &token_account, &payer, &[], 1)
+            .unwrap();
+        self.process(&[ix], &[]).await.unwrap();
+        (mint.pubkey(), token_account)
+    }
+
+    /// Creates an empty Token-2022 account of `mint` for `owner`.
+    pub async fn create_token_2022_account(&mut self, mint: Pubkey, owner: Pubkey) -> Pubkey {
+        let token_account = Keypair::new();
+        let payer = self.payer();
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(&[]).unwrap();
+        let ixs = [
+            system_instruction::create_account(
+                &payer,
+                &token_account.pubkey(),
+                1_000_000_000,
+                space as u64,
+                &spl_token_2022::id(),
+            ),
+            spl_token_2022::instruction::initialize_account3(&spl_token_2022::id(), &token_account.pubkey(), &mint, &owner)
+                .unwrap(),
+        ];
+        self.process(&ixs, &[&token_account]).await.unwrap();
+        token_account.pubkey()
+    }
+
+    pub async fn token_metadata(&mut self, mint: Pubkey) -> TokenMetadata {
+        let account = self.context.banks_client.get_account(mint).await.unwrap().unwrap();
+        let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data).unwrap();
+        state.get_variable_len_extension::<TokenMetadata>().unwrap()
+    }
+
+    fn create_mint_account(&mut self, mint_authority: Pubkey, freeze_authority: Option<Pubkey>, supply: u64) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        self.context.set_account(&mint, &mint_data(mint_authority, freeze_authority, supply).into());
+        mint
+    }
+
+    pub async fn mint_state(&mut self, mint: Pubkey) -> spl_token::state::Mint {
+        let account = self.context.banks_client.get_account(mint).await.unwrap().unwrap();
+        spl_token::state::Mint::unpack(&account.data).unwrap()
+    }
+
+    pub async fn token_account_state(&mut self, token_account: Pubkey) -> spl_token::state::Account {
+        let account = self.context.banks_client.get_account(token_account).await.unwrap().unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap()
+    }
+
+    pub fn create_token_account(&mut self, mint: Pubkey, owner: Pubkey, amount: u64) -> Pubkey {
+        let token_account = Pubkey::new_unique();
+        self.context.set_account(&token_account, &token_account_data(mint, owner, amount).into());
+        token_account
+    }
+
+    pub async fn token_balance(&mut self, token_account: Pubkey) -> u64 {
+        self.token_account_state(token_account).await.amount
+    }
+
+    pub async fn now(&mut self) -> i64 {
+        let clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp
+    }
+}
+
+/// Decodes every `T` emitted through `emit!` in `logs`.
+pub fn decode_events<T: Event>(logs: &[String]) -> Vec<T> {
+    logs.iter()
+        .filter_map(|line| line.split_once("Program data: ").map(|(_, data)| data))
+        .filter_map(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+        .filter(|bytes| bytes.starts_with(&T::DISCRIMINATOR))
+        .map(|bytes| T::deserialize(&mut &bytes[8..]).unwrap())
+        .collect()
+}
+
+pub fn assert_custom_error(result: Result<(), BanksClientError>, error: CustomError) {
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, u32::from(error), "expected {}", error.name());
+        }
+        other => panic!("expected {}, got {other:?}", error.name()),
+    }
+}
+
+pub fn assert_anchor_error(result: Result<(), BanksClientError>, error: anchor_lang::error::ErrorCode) {
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, u32::from(error), "expected {}", error.name());
+        }
+        other => panic!("expected {}, got {other:?}", error.name()),
+    }
+}
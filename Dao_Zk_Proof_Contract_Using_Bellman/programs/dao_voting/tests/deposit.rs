@@ -0,0 +1,99 @@
+mod common;
+
+use common::*;
+use dao_voting::errors::CustomError;
+use dao_voting::events::DepositSettled;
+use dao_voting::state::*;
+
+const DEPOSIT: u64 = 100_000_000;
+
+async fn setup(min_votes: u64) -> TestDao {
+    let mut dao = start(program_test(), 100).await;
+    let ix = dao.initialize_dao_ix(DaoConfig {
+        min_votes,
+        voting_period: 3600,
+        execution_delay: 0,
+        proposal_deposit: DEPOSIT,
+        ..DaoConfig::default()
+    });
+    let action = ProposalAction::SetSymbol { mint: dao.mint, symbol: "NEW".to_string() };
+    let ixs = [ix, dao.new_polling_ix(action)];
+    dao.process(&ixs, &[]).await.unwrap();
+    dao
+}
+
+async fn lamports(dao: &mut TestDao, address: solana_sdk::pubkey::Pubkey) -> u64 {
+    dao.context.banks_client.get_balance(address).await.unwrap()
+}
+
+async fn treasury_lamports(dao: &mut TestDao) -> u64 {
+    let treasury = treasury_address(&dao_address(&dao.mint));
+    lamports(dao, treasury).await
+}
+
+#[tokio::test]
+async fn refunds_deposit_when_quorum_is_reached() {
+    let mut dao = setup(1).await;
+    let election_key = election_address(&dao.payer());
+    let election: Election = dao.account(election_key).await;
+    assert_eq!(election.deposit, DEPOSIT);
+    let bonded = lamports(&mut dao, election_key).await;
+
+    let ix = dao.vote_ix(false);
+    dao.process(&[ix], &[]).await.unwrap();
+    let ix = dao.sum_up_ix().await;
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+
+    let events = decode_events::<DepositSettled>(&logs);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].amount, DEPOSIT);
+    assert!(events[0].refunded);
+
+    // Quorum is what counts, not the outcome
+    let election: Election = dao.account(election_key).await;
+    assert_eq!(election.state, ProposalState::Defeated);
+    assert_eq!(election.deposit, 0);
+    assert_eq!(lamports(&mut dao, election_key).await, bonded - DEPOSIT);
+    assert_eq!(treasury_lamports(&mut dao).await, 0);
+}
+
+#[tokio::test]
+async fn slashes_deposit_to_treasury_without_quorum() {
+    let mut dao = setup(2).await;
+    let ix = dao.vote_ix(true);
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    dao.set_clock(election.time + 1).await;
+    let ix = dao.sum_up_ix().await;
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+
+    let events = decode_events::<DepositSettled>(&logs);
+    assert_eq!(events.len(), 1);
+    assert!(!events[0].refunded);
+    assert_eq!(treasury_lamports(&mut dao).await, DEPOSIT);
+}
+
+#[tokio::test]
+async fn slashes_deposit_on_cancel() {
+    let mut dao = setup(1).await;
+
+    let ix = dao.cancel_proposal_ix(dao.payer()).await;
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert_eq!(election.deposit, 0);
+    assert_eq!(treasury_lamports(&mut dao).await, DEPOSIT);
+}
+
+#[tokio::test]
+async fn rejects_deposit_below_rent_minimum() {
+    let mut dao = start(program_test(), 100).await;
+    let ix = dao.initialize_dao_ix(DaoConfig {
+        min_votes: 1,
+        voting_period: 3600,
+        proposal_deposit: 1,
+        ..DaoConfig::default()
+    });
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidDaoConfig);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::*;
+use dao_voting::events::*;
+use dao_voting::state::*;
+use solana_sdk::signature::{Keypair, Signer};
+
+async fn setup(guardian: &Keypair) -> TestDao {
+    let mut dao = start(program_test(), 100).await;
+    dao.fund(guardian.pubkey());
+    let ix = dao.initialize_dao_ix(DaoConfig {
+        min_votes: 1,
+        voting_period: 3600,
+        execution_delay: 0,
+        guardian: guardian.pubkey(),
+        ..DaoConfig::default()
+    });
+    dao.process(&[ix], &[]).await.unwrap();
+    dao
+}
+
+#[tokio::test]
+async fn emits_proposal_lifecycle_events() {
+    let mut dao = setup(&Keypair::new()).await;
+    let election = election_address(&dao.payer());
+    let mint = dao.create_metadata_mint("Token", "OLD").await;
+    let action = ProposalAction::SetSymbol { mint, symbol: "NEW".to_string() };
+
+    let ix = dao.new_polling_ix(action.clone());
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    let created = decode_events::<ProposalCreated>(&logs);
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].election, election);
+    assert_eq!(created[0].creator, dao.payer());
+    assert_eq!(created[0].action, action);
+
+    let ix = dao.vote_ix(true);
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    let votes = decode_events::<VoteCast>(&logs);
+    assert_eq!(votes.len(), 1);
+    assert_eq!(votes[0].voter, dao.payer());
+    assert!(votes[0].choice);
+    assert_eq!(votes[0].weight, 100);
+    let rewards = decode_events::<RewardGranted>(&logs);
+    assert_eq!(rewards.len(), 1);
+    assert_eq!(rewards[0].reward_points, 1);
+
+    let ix = dao.sum_up_ix().await;
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    let finalized = decode_events::<ProposalFinalized>(&logs);
+    assert_eq!(finalized.len(), 1);
+    assert_eq!(finalized[0].state, ProposalState::Succeeded);
+    assert_eq!(finalized[0].current, 100);
+    assert_eq!(finalized[0].number_of_votes, 1);
+
+    let ix = dao.execute_metadata_ix(mint).await;
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    let executed = decode_events::<ProposalExecuted>(&logs);
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].election, election);
+    assert_eq!(executed[0].executor, dao.payer());
+}
+
+#[tokio::test]
+async fn emits_verifying_key_updates() {
+    let guardian = Keypair::new();
+    let mut dao = setup(&guardian).await;
+
+    let ix = dao.set_verifying_key_ix(guardian.pubkey(), dao.zk.verifying_key.clone());
+    let logs = dao.process_with_logs(&[ix], &[&guardian]).await;
+    let updates = decode_events::<VerifyingKeyUpdated>(&logs);
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].verifying_key, dao.verifying_key);
+    assert_eq!(updates[0].authority, guardian.pubkey());
+    assert_eq!(updates[0].version, 2);
+}
+
+#[tokio::test]
+async fn emits_cancellation_and_veto() {
+    let guardian = Keypair::new();
+    let mut dao = setup(&guardian).await;
+    let action = ProposalAction::SetName { mint: dao.mint, name: "New Name".to_string() };
+
+    let ix = dao.new_polling_ix(action);
+    dao.process(&[ix], &[]).await.unwrap();
+    let ix = dao.veto_proposal_ix(guardian.pubkey()).await;
+    let logs = dao.process_with_logs(&[ix], &[&guardian]).await;
+    let vetoes = decode_events::<ProposalVetoed>(&logs);
+    assert_eq!(vetoes.len(), 1);
+    assert_eq!(vetoes[0].guardian, guardian.pubkey());
+    assert_eq!(vetoes[0].previous_state, ProposalState::Voting);
+    assert!(decode_events::<ProposalCancelled>(&logs).is_empty());
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use common::*;
+use dao_voting::errors::CustomError;
+use dao_voting::state::*;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program_test::processor;
+use solana_sdk::account::Account;
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+
+// Copies the instruction data into the second account, provided the first one signed
+fn dummy_process(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (authority, target) = (&accounts[0], &accounts[1]);
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    target.try_borrow_mut_data()?[..data.len()].copy_from_slice(data);
+    Ok(())
+}
+
+struct Fixture {
+    dao: TestDao,
+    dummy_program: Pubkey,
+    target: Pubkey,
+    governance: Pubkey,
+}
+
+async fn setup() -> Fixture {
+    let dummy_program = Pubkey::new_unique();
+    let target = Pubkey::new_unique();
+    let mut program_test = program_test();
+    program_test.add_program("dummy", dummy_program, processor!(dummy_process));
+    program_test.add_account(
+        target,
+        Account {
+            lamports: 1_000_000_000,
+            data: vec![0; 8],
+            owner: dummy_program,
+            ..Account::default()
+        },
+    );
+
+    let mut dao = start(program_test, 100).await;
+    let ix = dao.initialize_dao_ix(DaoConfig {
+        min_votes: 1,
+        voting_period: 3600,
+        execution_delay: 0,
+        guardian: Pubkey::default(),
+        ..DaoConfig::default()
+    });
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let governance = governance_address(&dao_address(&dao.mint));
+    Fixture {
+        dao,
+        dummy_program,
+        target,
+        governance,
+    }
+}
+
+fn write_instruction(fixture: &Fixture, signer: Pubkey) -> ProposalAction {
+    ProposalAction::CustomInstruction {
+        instructions: vec![ProposalInstruction {
+            program_id: fixture.dummy_program,
+            accounts: vec![
+                ProposalAccountMeta { pubkey: signer, is_signer: true, is_writable: false },
+                ProposalAccountMeta { pubkey: fixture.target, is_signer: false, is_writable: true },
+            ],
+            data: vec![1, 2, 3, 4],
+        }],
+    }
+}
+
+fn remaining_accounts(fixture: &Fixture) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(fixture.governance, false),
+        AccountMeta::new(fixture.target, false),
+        AccountMeta::new_readonly(fixture.dummy_program, false),
+    ]
+}
+
+#[tokio::test]
+async fn executes_passed_proposal_with_governance_signer() {
+    let mut fixture = setup().await;
+    let action = write_instruction(&fixture, fixture.governance);
+    let ixs = [fixture.dao.new_polling_ix(action), fixture.dao.vote_ix(true), fixture.dao.sum_up_ix().await];
+    fixture.dao.process(&ixs, &[]).await.unwrap();
+
+    let ix = fixture.dao.execute_proposal_ix(remaining_accounts(&fixture)).await;
+    fixture.dao.process(&[ix], &[]).await.unwrap();
+
+    let target = fixture.dao.context.banks_client.get_account(fixture.target).await.unwrap().unwrap();
+    assert_eq!(target.data, vec![1, 2, 3, 4, 0, 0, 0, 0]);
+    let election: Election = fixture.dao.account(election_address(&fixture.dao.payer())).await;
+    assert!(election.executed);
+
+    // A second run must not replay the instructions
+    fixture.dao.context.get_new_latest_blockhash().await.unwrap();
+    let ix = fixture.dao.execute_proposal_ix(remaining_accounts(&fixture)).await;
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::ActionAlreadyExecuted);
+}
+
+#[tokio::test]
+async fn rejects_execution_of_defeated_proposal() {
+    let mut fixture = setup().await;
+    let action = write_instruction(&fixture, fixture.governance);
+    let ixs = [fixture.dao.new_polling_ix(action), fixture.dao.vote_ix(false), fixture.dao.sum_up_ix().await];
+    fixture.dao.process(&ixs, &[]).await.unwrap();
+
+    let ix = fixture.dao.execute_proposal_ix(remaining_accounts(&fixture)).await;
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::ProposalNotPassed);
+}
+
+#[tokio::test]
+async fn rejects_instructions_signed_by_other_accounts() {
+    let mut fixture = setup().await;
+    let action = write_instruction(&fixture, fixture.dao.payer());
+    let ix = fixture.dao.new_polling_ix(action);
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::InvalidAction);
+}
+
+#[tokio::test]
+async fn rejects_votes_after_voting_period() {
+    let mut fixture = setup().await;
+    let action = write_instruction(&fixture, fixture.governance);
+    let ix = fixture.dao.new_polling_ix(action);
+    fixture.dao.process(&[ix], &[]).await.unwrap();
+
+    // A late ballot could otherwise still flip the result before `to_sum_up`
+    let election: Election = fixture.dao.account(election_address(&fixture.dao.payer())).await;
+    fixture.dao.set_clock(election.time + 1).await;
+    let ix = fixture.dao.vote_ix(true);
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::VotingClosed);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use anchor_spl::token_2022::spl_token_2022;
+use common::*;
+use dao_voting::errors::CustomError;
+use dao_voting::state::*;
+use dao_voting::{ChangableToken, GovernedMint};
+use solana_program::account_info::AccountInfo;
+use solana_program::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use spl_token_2022::state::Account as Token2022Account;
+
+async fn setup() -> (TestDao, Pubkey) {
+    let mut dao = start(program_test(), 100).await;
+    let ix = dao.initialize_dao_ix(DaoConfig {
+        min_votes: 1,
+        voting_period: 3600,
+        ..DaoConfig::default()
+    });
+    dao.process(&[ix], &[]).await.unwrap();
+    let mint = dao.create_metadata_mint("Token", "TKN").await;
+    (dao, mint)
+}
+
+async fn pass(dao: &mut TestDao, action: ProposalAction) {
+    let ixs = [dao.new_polling_ix(action), dao.vote_ix(true), dao.sum_up_ix().await];
+    dao.process(&ixs, &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn renames_token_through_metadata_extension() {
+    let (mut dao, mint) = setup().await;
+    // Longer than the current name, so the mint has to grow
+    let name = "A Considerably Longer Token Name".to_string();
+    pass(&mut dao, ProposalAction::SetName { mint, name: name.clone() }).await;
+
+    let ix = dao.execute_metadata_ix(mint).await;
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let metadata = dao.token_metadata(mint).await;
+    assert_eq!(metadata.name, name);
+    assert_eq!(metadata.symbol, "TKN");
+    assert_eq!(Option::<Pubkey>::from(metadata.update_authority), Some(governance_address(&dao_address(&dao.mint))));
+}
+
+#[tokio::test]
+async fn rejects_mint_without_metadata() {
+    let (mut dao, _) = setup().await;
+    let (classic_mint, _) = dao.create_mint(10);
+    pass(&mut dao, ProposalAction::SetSymbol { mint: classic_mint, symbol: "NEW".to_string() }).await;
+
+    let ix = dao.execute_metadata_ix(classic_mint).await;
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidMintAccounts);
+}
+
+#[tokio::test]
+async fn rejects_metadata_mint_other_than_the_proposal() {
+    let (mut dao, mint) = setup().await;
+    pass(&mut dao, ProposalAction::SetSymbol { mint, symbol: "NEW".to_string() }).await;
+
+    let other = dao.create_metadata_mint("Other", "OTH").await;
+    let ix = dao.execute_metadata_ix(other).await;
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidMintAccounts);
+}
+
+#[tokio::test]
+async fn balance_of_reads_mint_supply() {
+    let (mut dao, mint) = setup().await;
+    let holder = Keypair::new();
+    let payer = dao.payer();
+    let ixs = [
+        system_instruction::create_account(
+            &payer,
+            &holder.pubkey(),
+            1_000_000_000,
+            Token2022Account::LEN as u64,
+            &spl_token_2022::id(),
+        ),
+        spl_token_2022::instruction::initialize_account3(&spl_token_2022::id(), &holder.pubkey(), &mint, &payer).unwrap(),
+        spl_token_2022::instruction::mint_to(&spl_token_2022::id(), &mint, &holder.pubkey(), &payer, &[], 75).unwrap(),
+    ];
+    dao.process(&ixs, &[&holder]).await.unwrap();
+
+    let mut account = dao.context.banks_client.get_account(mint).await.unwrap().unwrap();
+    let (mut lamports, mut other_lamports) = (0, 0);
+    let (mut empty, other) = (vec![], Pubkey::new_unique());
+    let mint_info = AccountInfo::new(&mint, false, false, &mut lamports, &mut account.data, &account.owner, false, 0);
+    let other_info = AccountInfo::new(&other, false, false, &mut other_lamports, &mut empty, &other, false, 0);
+    let token = GovernedMint {
+        mint: mint_info,
+        update_authority: other_info.clone(),
+        payer: other_info.clone(),
+        token_program: other_info.clone(),
+        system_program: other_info,
+        signer_seeds: &[],
+    };
+    assert_eq!(token.balance_of(payer).unwrap(), 75);
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::*;
+use dao_voting::errors::CustomError;
+use dao_voting::events::*;
+use dao_voting::state::*;
+use solana_program::program_option::COption;
+use solana_sdk::pubkey::Pubkey;
+use spl_token::state::AccountState;
+
+struct Fixture {
+    dao: TestDao,
+    mint: Pubkey,
+    holder: Pubkey,
+}
+
+async fn setup() -> Fixture {
+    let mut dao = start(program_test(), 100).await;
+    let ix = dao.initialize_dao_ix(DaoConfig {
+        min_votes: 1,
+        voting_period: 3600,
+        ..DaoConfig::default()
+    });
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let mint = dao.create_governed_mint();
+    let holder = dao.create_token_account(mint, Pubkey::new_unique(), 0);
+    Fixture { dao, mint, holder }
+}
+
+// Opens a proposal and votes it through
+async fn pass(dao: &mut TestDao, action: ProposalAction) {
+    let ixs = [dao.new_polling_ix(action), dao.vote_ix(true), dao.sum_up_ix().await];
+    dao.process(&ixs, &[]).await.unwrap();
+}
+
+// Closes the payer's election so the next proposal can reuse its address, with a fresh id
+async fn close(dao: &mut TestDao) {
+    let ix = dao.close_election_ix(dao.payer());
+    dao.process(&[ix], &[]).await.unwrap();
+    let now = dao.now().await;
+    dao.set_clock(now + 1).await;
+}
+
+#[tokio::test]
+async fn mints_to_recipient_with_governance_signature() {
+    let Fixture { mut dao, mint, holder } = setup().await;
+    pass(&mut dao, ProposalAction::MintTo { mint, amount: 50, recipient: holder }).await;
+
+    let ix = dao.execute_mint_ix(mint, Some(holder)).await;
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+
+    assert_eq!(dao.token_balance(holder).await, 50);
+    assert_eq!(dao.mint_state(mint).await.supply, 50);
+    let minted = decode_events::<TokensMinted>(&logs);
+    assert_eq!(minted.len(), 1);
+    assert_eq!(minted[0].recipient, holder);
+    assert_eq!(minted[0].amount, 50);
+}
+
+#[tokio::test]
+async fn freezes_and_thaws_token_account() {
+    let Fixture { mut dao, mint, holder } = setup().await;
+    pass(&mut dao, ProposalAction::FreezeAccount { mint, account: holder }).await;
+    let ix = dao.execute_mint_ix(mint, Some(holder)).await;
+    dao.process(&[ix], &[]).await.unwrap();
+    assert_eq!(dao.token_account_state(holder).await.state, AccountState::Frozen);
+
+    close(&mut dao).await;
+    pass(&mut dao, ProposalAction::ThawAccount { mint, account: holder }).await;
+    let ix = dao.execute_mint_ix(mint, Some(holder)).await;
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    assert_eq!(dao.token_account_state(holder).await.state, AccountState::Initialized);
+    let events = decode_events::<TokenAccountFrozen>(&logs);
+    assert_eq!(events.len(), 1);
+    assert!(!events[0].frozen);
+}
+
+#[tokio::test]
+async fn hands_over_and_revokes_authorities() {
+    let Fixture { mut dao, mint, .. } = setup().await;
+    let new_authority = Pubkey::new_unique();
+    let action = ProposalAction::SetMintAuthority {
+        mint,
+        authority_type: MintAuthorityType::FreezeAccount,
+        new_authority: Some(new_authority),
+    };
+    pass(&mut dao, action).await;
+    let ix = dao.execute_mint_ix(mint, None).await;
+    dao.process(&[ix], &[]).await.unwrap();
+    assert_eq!(dao.mint_state(mint).await.freeze_authority, COption::Some(new_authority));
+
+    close(&mut dao).await;
+    let action = ProposalAction::SetMintAuthority {
+        mint,
+        authority_type: MintAuthorityType::MintTokens,
+        new_authority: None,
+    };
+    pass(&mut dao, action).await;
+    let ix = dao.execute_mint_ix(mint, None).await;
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    assert_eq!(dao.mint_state(mint).await.mint_authority, COption::None);
+    let events = decode_events::<MintAuthorityChanged>(&logs);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].authority_type, MintAuthorityType::MintTokens);
+    assert_eq!(events[0].new_authority, None);
+}
+
+#[tokio::test]
+async fn rejects_mint_other_than_the_proposal() {
+    let Fixture { mut dao, mint, holder } = setup().await;
+    pass(&mut dao, ProposalAction::MintTo { mint, amount: 50, recipient: holder }).await;
+
+    let other_mint = dao.create_governed_mint();
+    let ix = dao.execute_mint_ix(other_mint, Some(holder)).await;
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidMintAccounts);
+}
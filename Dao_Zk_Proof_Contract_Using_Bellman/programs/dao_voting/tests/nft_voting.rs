@@ -0,0 +1,169 @@
+mod common;
+
+use anchor_spl::token_2022::spl_token_2022;
+use common::*;
+use dao_voting::errors::CustomError;
+use dao_voting::events::*;
+use dao_voting::state::*;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+
+struct Fixture {
+    dao: TestDao,
+    collection: Pubkey,
+    // Held by the payer
+    nfts: Vec<(Pubkey, Pubkey)>,
+}
+
+// Opens a proposal in a DAO voting by NFTs of a fresh collection, with two NFTs held by the payer
+async fn setup() -> Fixture {
+    let mut dao = start(program_test_with_token_groups(), 100).await;
+    let collection = dao.create_collection().await;
+    let ix = dao.initialize_dao_ix(DaoConfig {
+        min_votes: 1,
+        voting_period: 3600,
+        voting_power: VotingPower::NftCollection { collection },
+        ..DaoConfig::default()
+    });
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let payer = dao.payer();
+    let mut nfts = vec![];
+    for _ in 0..2 {
+        let (mint, token_account) = dao.create_nft(collection, payer).await;
+        nfts.push((token_account, mint));
+    }
+    let action = ProposalAction::SetName { mint: dao.mint, name: "New Name".to_string() };
+    let ix = dao.new_polling_nft_ix(action, nfts[0].0, nfts[0].1);
+    dao.process(&[ix], &[]).await.unwrap();
+    Fixture { dao, collection, nfts }
+}
+
+#[tokio::test]
+async fn counts_each_nft_as_one_vote() {
+    let mut fixture = setup().await;
+    let payer = fixture.dao.payer();
+    let ix = fixture.dao.vote_nft_ix(payer, true, &fixture.nfts).await;
+    let logs = fixture.dao.process_with_logs(&[ix], &[]).await;
+
+    let votes = decode_events::<VoteCast>(&logs);
+    assert_eq!(votes.len(), 1);
+    assert_eq!(votes[0].weight, 2);
+    let election: Election = fixture.dao.account(election_address(&payer)).await;
+    assert_eq!(election.votes_for, 2);
+    assert_eq!(election.number_of_votes, 1);
+
+    let mint = fixture.nfts[1].1;
+    let marker: NftVoteMarker = fixture.dao.account(nft_vote_marker_address(&payer, election.id, &mint)).await;
+    assert_eq!(marker.mint, mint);
+    assert_eq!(marker.voter, payer);
+}
+
+#[tokio::test]
+async fn rewards_split_nft_votes_once() {
+    let mut fixture = setup().await;
+    let payer = fixture.dao.payer();
+
+    let ix = fixture.dao.vote_nft_ix(payer, true, &fixture.nfts[..1]).await;
+    let logs = fixture.dao.process_with_logs(&[ix], &[]).await;
+    assert_eq!(decode_events::<RewardGranted>(&logs).len(), 1);
+    let ix = fixture.dao.vote_nft_ix(payer, true, &fixture.nfts[1..]).await;
+    let logs = fixture.dao.process_with_logs(&[ix], &[]).await;
+    assert!(decode_events::<RewardGranted>(&logs).is_empty());
+
+    let election: Election = fixture.dao.account(election_address(&payer)).await;
+    assert_eq!(election.votes_for, 2);
+    assert_eq!(election.number_of_votes, 1);
+    let user: User = fixture.dao.account(user_address(&payer)).await;
+    assert_eq!(user.reward_points, 1);
+}
+
+#[tokio::test]
+async fn rejects_nft_voting_twice() {
+    let mut fixture = setup().await;
+    let payer = fixture.dao.payer();
+    let nft = fixture.nfts[0];
+
+    // Listing the same NFT twice in one vote
+    let ix = fixture.dao.vote_nft_ix(payer, true, &[nft, nft]).await;
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::NftAlreadyVoted);
+
+    let ix = fixture.dao.vote_nft_ix(payer, true, &[nft]).await;
+    fixture.dao.process(&[ix], &[]).await.unwrap();
+
+    // Handing the NFT to another wallet after voting
+    let other = Keypair::new();
+    let token_account = fixture.dao.create_token_2022_account(nft.1, other.pubkey()).await;
+    let ixs = [
+        spl_token_2022::instruction::transfer_checked(
+            &spl_token_2022::id(),
+            &nft.0,
+            &nft.1,
+            &token_account,
+            &payer,
+            &[],
+            1,
+            0,
+        )
+        .unwrap(),
+        system_instruction::transfer(&payer, &other.pubkey(), 1_000_000_000),
+    ];
+    fixture.dao.process(&ixs, &[]).await.unwrap();
+
+    let ix = fixture.dao.vote_nft_ix(other.pubkey(), false, &[(token_account, nft.1)]).await;
+    assert_custom_error(fixture.dao.process(&[ix], &[&other]).await, CustomError::NftAlreadyVoted);
+}
+
+#[tokio::test]
+async fn votes_through_prefunded_marker() {
+    let mut fixture = setup().await;
+    let payer = fixture.dao.payer();
+    let nft = fixture.nfts[0];
+    let id = fixture.dao.election_id().await;
+
+    // Lamports sent to the marker address must not lock the NFT out
+    let ix = system_instruction::transfer(&payer, &nft_vote_marker_address(&payer, id, &nft.1), 1_000_000);
+    fixture.dao.process(&[ix], &[]).await.unwrap();
+
+    let ix = fixture.dao.vote_nft_ix(payer, true, &[nft]).await;
+    fixture.dao.process(&[ix], &[]).await.unwrap();
+    let election: Election = fixture.dao.account(election_address(&payer)).await;
+    assert_eq!(election.votes_for, 1);
+}
+
+#[tokio::test]
+async fn rejects_nfts_outside_collection_or_wallet() {
+    let mut fixture = setup().await;
+    let payer = fixture.dao.payer();
+
+    let other_collection = fixture.dao.create_collection().await;
+    let (mint, token_account) = fixture.dao.create_nft(other_collection, payer).await;
+    let ix = fixture.dao.vote_nft_ix(payer, true, &[(token_account, mint)]).await;
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::InvalidNftAccounts);
+
+    let (mint, token_account) = fixture.dao.create_nft(fixture.collection, Pubkey::new_unique()).await;
+    let ix = fixture.dao.vote_nft_ix(payer, true, &[(token_account, mint)]).await;
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::InvalidNftAccounts);
+
+    let ix = fixture.dao.vote_nft_ix(payer, true, &[]).await;
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::InvalidNftAccounts);
+}
+
+#[tokio::test]
+async fn rejects_token_votes_in_nft_dao() {
+    let mut fixture = setup().await;
+    let ix = fixture.dao.vote_ix(true);
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::WrongVotingPower);
+}
+
+#[tokio::test]
+async fn rejects_nft_votes_after_voting_period() {
+    let mut fixture = setup().await;
+    let payer = fixture.dao.payer();
+    let election: Election = fixture.dao.account(election_address(&payer)).await;
+    fixture.dao.set_clock(election.time + 1).await;
+
+    let ix = fixture.dao.vote_nft_ix(payer, true, &fixture.nfts).await;
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::VotingClosed);
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use common::*;
+use dao_voting::state::*;
+use solana_sdk::clock::Clock;
+use solana_sdk::pubkey::Pubkey;
+
+async fn setup() -> TestDao {
+    let mut dao = start(program_test(), 100).await;
+    let ix = dao.initialize_dao_ix(DaoConfig {
+        min_votes: 1,
+        voting_period: 3600,
+        execution_delay: 0,
+        guardian: Pubkey::default(),
+        ..DaoConfig::default()
+    });
+    dao.process(&[ix], &[]).await.unwrap();
+    dao
+}
+
+#[tokio::test]
+async fn finalization_writes_result_that_survives_close() {
+    let mut dao = setup().await;
+    let action = ProposalAction::SetDaoConfig { config: DaoConfig { min_votes: 2, ..DaoConfig::default() } };
+    let ixs = [dao.new_polling_ix(action), dao.vote_ix(true), dao.sum_up_ix().await];
+    dao.process(&ixs, &[]).await.unwrap();
+
+    let clock: Clock = dao.context.banks_client.get_sysvar().await.unwrap();
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    let address = result_address(&dao.payer(), election.id);
+    let result: ProposalResult = dao.account(address).await;
+    assert_eq!(result.election, election_address(&dao.payer()));
+    assert_eq!(result.dao, dao_address(&dao.mint));
+    assert_eq!(result.id, election.id);
+    assert_eq!(result.creator, dao.payer());
+    assert_eq!(result.state, ProposalState::Succeeded);
+    assert_eq!(result.votes_for, 100);
+    assert_eq!(result.votes_against, 0);
+    assert_eq!(result.current, 100);
+    assert_eq!(result.number_of_votes, 1);
+    assert_eq!(result.vk_version, 1);
+    assert_eq!(result.finalized_slot, clock.slot);
+    assert_eq!(result.finalized_at, clock.unix_timestamp);
+    assert!(!result.executed);
+
+    // Only an executed proposal can be closed
+    let ix = dao.execute_proposal_ix(vec![]).await;
+    dao.process(&[ix], &[]).await.unwrap();
+    let ix = dao.close_election_ix(dao.payer());
+    dao.process(&[ix], &[]).await.unwrap();
+    let closed = dao.context.banks_client.get_account(election_address(&dao.payer())).await.unwrap();
+    assert!(closed.is_none());
+    let archived: ProposalResult = dao.account(address).await;
+    assert_eq!(archived.state, ProposalState::Succeeded);
+    assert_eq!(archived.votes_for, 100);
+    assert!(archived.executed);
+}
+
+#[tokio::test]
+async fn get_results_leaves_tally_untouched() {
+    let mut dao = setup().await;
+    let action = ProposalAction::SetName { mint: dao.mint, name: "New Name".to_string() };
+    let ixs = [dao.new_polling_ix(action), dao.vote_ix(false), dao.sum_up_ix().await];
+    dao.process(&ixs, &[]).await.unwrap();
+
+    let ix = dao.get_results_ix();
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert_eq!(election.state, ProposalState::Defeated);
+    assert_eq!(election.current, -100);
+    assert_eq!(election.votes_against, 100);
+    assert_eq!(election.number_of_votes, 1);
+    assert_eq!(election.voters, vec![dao.payer()]);
+
+    let result: ProposalResult = dao.account(result_address(&dao.payer(), election.id)).await;
+    assert_eq!(result.state, ProposalState::Defeated);
+    assert_eq!(result.votes_against, 100);
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use common::*;
+use dao_voting::errors::CustomError;
+use dao_voting::events::*;
+use dao_voting::state::*;
+use solana_sdk::pubkey::Pubkey;
+
+const GRANT: u64 = 600;
+const DURATION: i64 = 600;
+
+struct Fixture {
+    dao: TestDao,
+    mint: Pubkey,
+    recipient: Pubkey,
+    stream: Pubkey,
+    start: i64,
+}
+
+// Funds the treasury with 1000 tokens and executes a `GRANT` stream to a fresh recipient
+async fn setup() -> Fixture {
+    let mut dao = start(program_test(), 100).await;
+    let ix = dao.initialize_dao_ix(DaoConfig {
+        min_votes: 1,
+        voting_period: 3600,
+        max_transfer_per_proposal: 1000,
+        max_transfer_per_epoch: 1000,
+        ..DaoConfig::default()
+    });
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let (mint, depositor_token_account) = dao.create_mint(1000);
+    let ix = dao.deposit_ix(mint, depositor_token_account, 1000);
+    dao.process(&[ix], &[]).await.unwrap();
+    let recipient = dao.create_token_account(mint, Pubkey::new_unique(), 0);
+
+    let action = ProposalAction::StreamFromTreasury { mint, amount: GRANT, recipient, duration: DURATION };
+    let ixs = [dao.new_polling_ix(action), dao.vote_ix(true), dao.sum_up_ix().await];
+    dao.process(&ixs, &[]).await.unwrap();
+    let ix = dao.execute_stream_ix(mint).await;
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+
+    let created = decode_events::<StreamCreated>(&logs);
+    assert_eq!(created.len(), 1);
+    let stream = created[0].stream;
+    assert_eq!(stream, stream_address(&dao.payer(), dao.election_id().await));
+    let start = created[0].start;
+    assert_eq!(created[0].end, start + DURATION);
+
+    Fixture { dao, mint, recipient, stream, start }
+}
+
+// Opens and passes another proposal, after closing the stream's election
+async fn pass_next(fixture: &mut Fixture, action: ProposalAction) {
+    let dao = &mut fixture.dao;
+    let ix = dao.close_election_ix(dao.payer());
+    dao.process(&[ix], &[]).await.unwrap();
+    let now = dao.now().await;
+    dao.set_clock(now + 1).await;
+
+    let ixs = [dao.new_polling_ix(action), dao.vote_ix(true), dao.sum_up_ix().await];
+    dao.process(&ixs, &[]).await.unwrap();
+}
+
+async fn claim(fixture: &mut Fixture) -> Result<(), solana_program_test::BanksClientError> {
+    let ix = fixture.dao.claim_stream_ix(fixture.stream, fixture.mint, fixture.recipient);
+    fixture.dao.process(&[ix], &[]).await
+}
+
+async fn spending(fixture: &mut Fixture) -> TreasurySpending {
+    let dao = dao_address(&fixture.dao.mint);
+    fixture.dao.account(spending_address(&dao, &fixture.mint)).await
+}
+
+#[tokio::test]
+async fn claims_vest_linearly() {
+    let mut fixture = setup().await;
+    assert_custom_error(claim(&mut fixture).await, CustomError::NothingToClaim);
+    assert_eq!(spending(&mut fixture).await.reserved, GRANT);
+
+    fixture.dao.set_clock(fixture.start + DURATION / 2).await;
+    claim(&mut fixture).await.unwrap();
+    assert_eq!(fixture.dao.token_balance(fixture.recipient).await, GRANT / 2);
+
+    fixture.dao.set_clock(fixture.start + 2 * DURATION).await;
+    claim(&mut fixture).await.unwrap();
+    assert_eq!(fixture.dao.token_balance(fixture.recipient).await, GRANT);
+
+    let stream: Stream = fixture.dao.account(fixture.stream).await;
+    assert_eq!(stream.claimed, GRANT);
+    assert_eq!(spending(&mut fixture).await.reserved, 0);
+}
+
+#[tokio::test]
+async fn reserved_funds_cannot_be_paid_out_twice() {
+    let mut fixture = setup().await;
+    let action = ProposalAction::TransferFromTreasury {
+        mint: fixture.mint,
+        amount: 500,
+        recipient: fixture.recipient,
+    };
+    pass_next(&mut fixture, action).await;
+
+    // The treasury holds 1000, but 600 of it is owed to the stream
+    let ix = fixture.dao.execute_transfer_ix(fixture.mint, fixture.recipient).await;
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::InsufficientBalance);
+}
+
+#[tokio::test]
+async fn cancel_stream_releases_unvested_funds() {
+    let mut fixture = setup().await;
+    fixture.dao.set_clock(fixture.start + 150).await;
+    claim(&mut fixture).await.unwrap();
+
+    let action = ProposalAction::CancelStream { stream: fixture.stream };
+    pass_next(&mut fixture, action).await;
+    let ix = fixture.dao.execute_proposal_ix(vec![]).await;
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::ActionNotSupported);
+
+    fixture.dao.set_clock(fixture.start + 300).await;
+    let ix = fixture.dao.cancel_stream_ix(fixture.stream, fixture.mint).await;
+    let logs = fixture.dao.process_with_logs(&[ix], &[]).await;
+    let cancelled = decode_events::<StreamCancelled>(&logs);
+    assert_eq!(cancelled.len(), 1);
+    assert_eq!(cancelled[0].vested, 300);
+    assert_eq!(cancelled[0].returned, 300);
+    assert_eq!(spending(&mut fixture).await.reserved, 150);
+
+    // Vested but unclaimed tokens stay claimable, nothing more accrues
+    fixture.dao.set_clock(fixture.start + 2 * DURATION).await;
+    claim(&mut fixture).await.unwrap();
+    assert_eq!(fixture.dao.token_balance(fixture.recipient).await, 300);
+    let ix = fixture.dao.claim_stream_ix(fixture.stream, fixture.mint, fixture.recipient);
+    fixture.dao.context.get_new_latest_blockhash().await.unwrap();
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::NothingToClaim);
+}
+
+#[tokio::test]
+async fn cancel_stream_requires_matching_proposal() {
+    let mut fixture = setup().await;
+    let other = Pubkey::new_unique();
+    pass_next(&mut fixture, ProposalAction::CancelStream { stream: other }).await;
+
+    let ix = fixture.dao.cancel_stream_ix(fixture.stream, fixture.mint).await;
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::InvalidAction);
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::*;
+use dao_voting::errors::CustomError;
+use dao_voting::state::*;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+const DELAY: i64 = 86_400;
+
+async fn setup(guardian: &Keypair) -> (TestDao, Pubkey) {
+    let mut dao = start(program_test(), 100).await;
+    dao.fund(guardian.pubkey());
+    let ix = dao.initialize_dao_ix(DaoConfig {
+        min_votes: 1,
+        voting_period: 3600,
+        execution_delay: DELAY,
+        guardian: guardian.pubkey(),
+        ..DaoConfig::default()
+    });
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let mint = dao.create_metadata_mint("Token", "OLD").await;
+    let action = ProposalAction::SetSymbol { mint, symbol: "NEW".to_string() };
+    let ixs = [dao.new_polling_ix(action), dao.vote_ix(true), dao.sum_up_ix().await];
+    dao.process(&ixs, &[]).await.unwrap();
+    (dao, mint)
+}
+
+#[tokio::test]
+async fn queues_succeeded_proposal_until_delay_elapses() {
+    let guardian = Keypair::new();
+    let (mut dao, mint) = setup(&guardian).await;
+    let now = dao.now().await;
+
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert_eq!(election.state, ProposalState::Succeeded);
+    assert_eq!(election.executable_at, now + DELAY);
+    assert!(!election.vote_active);
+
+    let ix = dao.execute_metadata_ix(mint).await;
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::TimelockActive);
+
+    dao.set_clock(now + DELAY).await;
+    let ix = dao.execute_metadata_ix(mint).await;
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let metadata = dao.token_metadata(mint).await;
+    assert_eq!(metadata.symbol, "NEW");
+    assert_eq!(metadata.name, "Token");
+}
+
+#[tokio::test]
+async fn guardian_vetoes_during_delay() {
+    let guardian = Keypair::new();
+    let (mut dao, mint) = setup(&guardian).await;
+
+    let ix = dao.veto_proposal_ix(guardian.pubkey()).await;
+    dao.process(&[ix], &[&guardian]).await.unwrap();
+
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert_eq!(election.state, ProposalState::Vetoed);
+    // The result written at finalization follows the veto, and keeps the tally
+    let result: ProposalResult = dao.account(result_address(&dao.payer(), election.id)).await;
+    assert_eq!(result.state, ProposalState::Vetoed);
+    assert_eq!(result.votes_for, 100);
+
+    let now = dao.now().await;
+    dao.set_clock(now + DELAY).await;
+    let ix = dao.execute_metadata_ix(mint).await;
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidProposalState);
+}
+
+#[tokio::test]
+async fn guardian_cannot_veto_after_delay() {
+    let guardian = Keypair::new();
+    let (mut dao, _) = setup(&guardian).await;
+
+    let now = dao.now().await;
+    dao.set_clock(now + DELAY).await;
+    let ix = dao.veto_proposal_ix(guardian.pubkey()).await;
+    assert_custom_error(dao.process(&[ix], &[&guardian]).await, CustomError::TimelockElapsed);
+}
+
+#[tokio::test]
+async fn rejects_veto_from_non_guardian() {
+    let guardian = Keypair::new();
+    let (mut dao, _) = setup(&guardian).await;
+
+    let impostor = Keypair::new();
+    dao.fund(impostor.pubkey());
+    let ix = dao.veto_proposal_ix(impostor.pubkey()).await;
+    assert_custom_error(dao.process(&[ix], &[&impostor]).await, CustomError::Unauthorized);
+}
+
+#[tokio::test]
+async fn queued_proposal_stays_open_until_executed() {
+    let guardian = Keypair::new();
+    let (mut dao, mint) = setup(&guardian).await;
+
+    let ix = dao.close_election_ix(dao.payer());
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidProposalState);
+
+    let now = dao.now().await;
+    dao.set_clock(now + DELAY).await;
+    let ixs = [dao.execute_metadata_ix(mint).await, dao.close_election_ix(dao.payer())];
+    dao.process(&ixs, &[]).await.unwrap();
+    let closed = dao.context.banks_client.get_account(election_address(&dao.payer())).await.unwrap();
+    assert!(closed.is_none());
+}
+
+#[tokio::test]
+async fn only_creator_closes_election() {
+    let guardian = Keypair::new();
+    let (mut dao, _) = setup(&guardian).await;
+    let ix = dao.veto_proposal_ix(guardian.pubkey()).await;
+    dao.process(&[ix], &[&guardian]).await.unwrap();
+
+    let other = Keypair::new();
+    let ix = dao.close_election_ix(other.pubkey());
+    assert_custom_error(dao.process(&[ix], &[&other]).await, CustomError::Unauthorized);
+    let ix = dao.close_election_ix(dao.payer());
+    dao.process(&[ix], &[]).await.unwrap();
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_spl::token_2022::spl_token_2022::extension::ExtensionType;
+use common::*;
+use dao_voting::errors::CustomError;
+use dao_voting::events::VoteCast;
+use dao_voting::state::*;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+
+fn config() -> DaoConfig {
+    DaoConfig {
+        min_votes: 1,
+        voting_period: 3600,
+        ..DaoConfig::default()
+    }
+}
+
+fn action(dao: &TestDao) -> ProposalAction {
+    ProposalAction::SetSymbol { mint: dao.mint, symbol: "NEW".to_string() }
+}
+
+#[tokio::test]
+async fn votes_with_token_2022_balance() {
+    let mut dao = start_token_2022(program_test(), 70, &[]).await;
+    let ixs = [dao.initialize_dao_ix(config()), dao.new_polling_ix(action(&dao))];
+    dao.process(&ixs, &[]).await.unwrap();
+
+    let ix = dao.vote_ix(true);
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    let votes = decode_events::<VoteCast>(&logs);
+    assert_eq!(votes.len(), 1);
+    assert_eq!(votes[0].weight, 70);
+}
+
+#[tokio::test]
+async fn rejects_non_transferable_governing_mint() {
+    let mut dao = start_token_2022(program_test(), 70, &[ExtensionType::NonTransferable]).await;
+    let ix = dao.initialize_dao_ix(config());
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidGoverningMint);
+}
+
+#[tokio::test]
+async fn rejects_transfer_hook_governing_mint() {
+    let mut dao = start_token_2022(program_test(), 70, &[ExtensionType::TransferHook]).await;
+    let ix = dao.initialize_dao_ix(config());
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidGoverningMint);
+}
+
+#[tokio::test]
+async fn only_mint_authority_initializes_dao() {
+    let mut dao = start(program_test(), 70).await;
+    let other = Keypair::new();
+    let ixs = [
+        system_instruction::transfer(&dao.payer(), &other.pubkey(), 1_000_000_000),
+        dao.initialize_dao_as_ix(other.pubkey(), config()),
+    ];
+    assert_custom_error(dao.process(&ixs, &[&other]).await, CustomError::Unauthorized);
+
+    let ix = dao.initialize_dao_ix(config());
+    dao.process(&[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn rejects_token_accounts_of_other_mints_or_owners() {
+    let mut dao = start(program_test(), 100).await;
+    let ixs = [dao.initialize_dao_ix(config()), dao.new_polling_ix(action(&dao))];
+    dao.process(&ixs, &[]).await.unwrap();
+    let governing = dao.token_account;
+
+    let (_, other_mint_account) = dao.create_mint(100);
+    dao.token_account = other_mint_account;
+    let ix = dao.vote_ix(true);
+    assert_anchor_error(dao.process(&[ix], &[]).await, ErrorCode::ConstraintTokenMint);
+
+    // Someone else's balance of the governing mint
+    dao.token_account = dao.create_token_account(dao.mint, Pubkey::new_unique(), 100);
+    let ix = dao.vote_ix(true);
+    assert_anchor_error(dao.process(&[ix], &[]).await, ErrorCode::ConstraintTokenOwner);
+
+    dao.token_account = governing;
+    let ix = dao.vote_ix(true);
+    dao.process(&[ix], &[]).await.unwrap();
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use dao_voting::errors::CustomError;
+use dao_voting::events::*;
+use dao_voting::state::*;
+use solana_sdk::pubkey::Pubkey;
+
+struct Fixture {
+    dao: TestDao,
+    mint: Pubkey,
+    recipient: Pubkey,
+}
+
+async fn setup() -> Fixture {
+    let mut dao = start(program_test(), 100).await;
+    let ix = dao.initialize_dao_ix(DaoConfig {
+        min_votes: 1,
+        voting_period: 3600,
+        max_transfer_per_proposal: 60,
+        max_transfer_per_epoch: 100,
+        ..DaoConfig::default()
+    });
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let (mint, depositor_token_account) = dao.create_mint(500);
+    let ix = dao.deposit_ix(mint, depositor_token_account, 200);
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    let deposits = decode_events::<TreasuryDeposited>(&logs);
+    assert_eq!(deposits.len(), 1);
+    assert_eq!(deposits[0].amount, 200);
+
+    let recipient = dao.create_token_account(mint, Pubkey::new_unique(), 0);
+    Fixture { dao, mint, recipient }
+}
+
+// Opens a transfer proposal and votes it through
+async fn pass_transfer(fixture: &mut Fixture, amount: u64) {
+    let action = ProposalAction::TransferFromTreasury {
+        mint: fixture.mint,
+        amount,
+        recipient: fixture.recipient,
+    };
+    let dao = &mut fixture.dao;
+    let ixs = [dao.new_polling_ix(action), dao.vote_ix(true), dao.sum_up_ix().await];
+    dao.process(&ixs, &[]).await.unwrap();
+}
+
+// Closes the payer's election so the next proposal can reuse its address, with a fresh id
+async fn close(fixture: &mut Fixture) {
+    let dao = &mut fixture.dao;
+    let ix = dao.close_election_ix(dao.payer());
+    dao.process(&[ix], &[]).await.unwrap();
+    let now = dao.now().await;
+    dao.set_clock(now + 1).await;
+}
+
+#[tokio::test]
+async fn deposits_and_pays_out_passed_transfer() {
+    let mut fixture = setup().await;
+    let treasury = get_associated_token_address(&treasury_address(&dao_address(&fixture.dao.mint)), &fixture.mint);
+    assert_eq!(fixture.dao.token_balance(treasury).await, 200);
+
+    pass_transfer(&mut fixture, 60).await;
+    let ix = fixture.dao.execute_transfer_ix(fixture.mint, fixture.recipient).await;
+    let logs = fixture.dao.process_with_logs(&[ix], &[]).await;
+
+    assert_eq!(fixture.dao.token_balance(fixture.recipient).await, 60);
+    assert_eq!(fixture.dao.token_balance(treasury).await, 140);
+    let transfers = decode_events::<TreasuryTransferred>(&logs);
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].recipient, fixture.recipient);
+    assert_eq!(transfers[0].epoch_spent, 60);
+}
+
+#[tokio::test]
+async fn enforces_per_epoch_cap() {
+    let mut fixture = setup().await;
+    pass_transfer(&mut fixture, 60).await;
+    let ix = fixture.dao.execute_transfer_ix(fixture.mint, fixture.recipient).await;
+    fixture.dao.process(&[ix], &[]).await.unwrap();
+    close(&mut fixture).await;
+
+    // 60 + 60 goes over the 100 allowed per epoch
+    pass_transfer(&mut fixture, 60).await;
+    let ix = fixture.dao.execute_transfer_ix(fixture.mint, fixture.recipient).await;
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::SpendingCapExceeded);
+
+    fixture.dao.set_epoch(1).await;
+    let ix = fixture.dao.execute_transfer_ix(fixture.mint, fixture.recipient).await;
+    fixture.dao.process(&[ix], &[]).await.unwrap();
+    assert_eq!(fixture.dao.token_balance(fixture.recipient).await, 120);
+
+    let dao = dao_address(&fixture.dao.mint);
+    let spending: TreasurySpending = fixture.dao.account(spending_address(&dao, &fixture.mint)).await;
+    assert_eq!(spending.epoch, 1);
+    assert_eq!(spending.spent, 60);
+}
+
+#[tokio::test]
+async fn rejects_transfers_over_per_proposal_cap() {
+    let mut fixture = setup().await;
+    let action = ProposalAction::TransferFromTreasury {
+        mint: fixture.mint,
+        amount: 61,
+        recipient: fixture.recipient,
+    };
+    let ix = fixture.dao.new_polling_ix(action);
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::SpendingCapExceeded);
+}
+
+#[tokio::test]
+async fn rejects_payout_to_other_recipient() {
+    let mut fixture = setup().await;
+    pass_transfer(&mut fixture, 60).await;
+
+    let other = fixture.dao.create_token_account(fixture.mint, Pubkey::new_unique(), 0);
+    let ix = fixture.dao.execute_transfer_ix(fixture.mint, other).await;
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::InvalidTreasuryAccounts);
+
+    let ix = fixture.dao.execute_proposal_ix(vec![]).await;
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::InvalidTreasuryAccounts);
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use anchor_lang::AnchorDeserialize;
+use common::*;
+use dao_voting::state::*;
+use solana_sdk::pubkey::Pubkey;
+
+// Queues a passed proposal behind a delay, during which `guardian` can still veto it
+async fn finalize(mut dao: TestDao, vote: bool, guardian: Pubkey) -> (TestDao, Pubkey) {
+    let ix = dao.initialize_dao_ix(DaoConfig {
+        min_votes: 1,
+        voting_period: 3600,
+        execution_delay: 3600,
+        guardian,
+        ..DaoConfig::default()
+    });
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let action = ProposalAction::SetSymbol { mint: dao.mint, symbol: "NEW".to_string() };
+    let ixs = [dao.new_polling_ix(action), dao.vote_ix(vote), dao.sum_up_ix().await];
+    dao.process(&ixs, &[]).await.unwrap();
+
+    let id = dao.election_id().await;
+    let result = result_address(&dao.payer(), id);
+    (dao, result)
+}
+
+#[tokio::test]
+async fn returns_result_through_return_data() {
+    let (mut dao, result) = finalize(start(program_test(), 100).await, true, Pubkey::default()).await;
+
+    let ix = dao.view_result_ix(result);
+    let data = dao.process_with_return_data(&[ix], &[]).await;
+    let returned = ProposalResult::try_from_slice(&data).unwrap();
+
+    let stored: ProposalResult = dao.account(result).await;
+    assert_eq!(returned.election, stored.election);
+    assert_eq!(returned.id, stored.id);
+    assert_eq!(returned.state, ProposalState::Succeeded);
+    assert_eq!(returned.votes_for, 100);
+    assert_eq!(returned.finalized_slot, stored.finalized_slot);
+}
+
+mod cpi {
+    use super::*;
+    use anchor_lang::AnchorSerialize;
+    use dao_voting::errors::CustomError;
+    use solana_program::account_info::AccountInfo;
+    use solana_program::entrypoint::ProgramResult;
+    use solana_program_test::processor;
+    use solana_sdk::account::Account;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::signature::{Keypair, Signer};
+
+    // Reads the outcome through `dao_voting::outcome` and stores it in the third account
+    fn consumer_process(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+        let (program, result, target) = (accounts[0].clone(), accounts[1].clone(), &accounts[2]);
+        let outcome = if data[0] == 1 {
+            dao_voting::outcome::require_passed(program, result)?
+        } else {
+            dao_voting::outcome::view_result(program, result)?
+        };
+        let bytes = outcome.try_to_vec()?;
+        target.try_borrow_mut_data()?[..bytes.len()].copy_from_slice(&bytes);
+        Ok(())
+    }
+
+    async fn setup(vote: bool, guardian: Pubkey) -> (TestDao, Pubkey, Pubkey, Pubkey) {
+        let consumer = Pubkey::new_unique();
+        let target = Pubkey::new_unique();
+        let mut program_test = program_test();
+        program_test.add_program("consumer", consumer, processor!(consumer_process));
+        program_test.add_account(
+            target,
+            Account {
+                lamports: 1_000_000_000,
+                data: vec![0; 256],
+                owner: consumer,
+                ..Account::default()
+            },
+        );
+        let (dao, result) = finalize(start(program_test, 100).await, vote, guardian).await;
+        (dao, consumer, target, result)
+    }
+
+    fn consumer_ix(consumer: Pubkey, target: Pubkey, result: Pubkey, require_passed: bool) -> Instruction {
+        Instruction {
+            program_id: consumer,
+            accounts: vec![
+                AccountMeta::new_readonly(dao_voting::ID, false),
+                AccountMeta::new_readonly(result, false),
+                AccountMeta::new(target, false),
+            ],
+            data: vec![require_passed as u8],
+        }
+    }
+
+    #[tokio::test]
+    async fn dependent_program_reads_outcome() {
+        let (mut dao, consumer, target, result) = setup(true, Pubkey::default()).await;
+
+        let ix = consumer_ix(consumer, target, result, true);
+        dao.process(&[ix], &[]).await.unwrap();
+
+        let account = dao.context.banks_client.get_account(target).await.unwrap().unwrap();
+        let outcome = ProposalResult::deserialize(&mut account.data.as_slice()).unwrap();
+        let stored: ProposalResult = dao.account(result).await;
+        assert_eq!(outcome.election, stored.election);
+        assert_eq!(outcome.state, ProposalState::Succeeded);
+        assert_eq!(outcome.votes_for, 100);
+    }
+
+    #[tokio::test]
+    async fn require_passed_rejects_defeated_proposal() {
+        let (mut dao, consumer, target, result) = setup(false, Pubkey::default()).await;
+
+        let ix = consumer_ix(consumer, target, result, false);
+        dao.process(&[ix], &[]).await.unwrap();
+
+        let ix = consumer_ix(consumer, target, result, true);
+        assert_custom_error(dao.process(&[ix], &[]).await, CustomError::ProposalNotPassed);
+    }
+
+    #[tokio::test]
+    async fn require_passed_rejects_vetoed_proposal() {
+        let guardian = Keypair::new();
+        let (mut dao, consumer, target, result) = setup(true, guardian.pubkey()).await;
+        let ix = consumer_ix(consumer, target, result, true);
+        dao.process(&[ix], &[]).await.unwrap();
+
+        // Vetoed during the execution delay, after it was finalized as passed
+        dao.fund(guardian.pubkey());
+        let ix = dao.veto_proposal_ix(guardian.pubkey()).await;
+        dao.process(&[ix], &[&guardian]).await.unwrap();
+
+        // Otherwise the bank replays the first call from its status cache
+        dao.context.get_new_latest_blockhash().await.unwrap();
+        let ix = consumer_ix(consumer, target, result, true);
+        assert_custom_error(dao.process(&[ix], &[]).await, CustomError::ProposalNotPassed);
+    }
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_spl::token_2022::spl_token_2022::extension::ExtensionType;
+use common::*;
+use dao_voting::errors::CustomError;
+use dao_voting::events::*;
+use dao_voting::state::*;
+use solana_sdk::pubkey::Pubkey;
+
+struct Fixture {
+    dao: TestDao,
+    // Payer's token accounts of the weighted mints
+    lp_token_account: Pubkey,
+    bonus_token_account: Pubkey,
+}
+
+// Opens a proposal in a DAO that counts an LP mint three times and a bonus mint twice
+async fn setup() -> Fixture {
+    let mut dao = start(program_test(), 100).await;
+    let (lp_mint, lp_token_account) = dao.create_mint(50);
+    let (bonus_mint, bonus_token_account) = dao.create_mint(10);
+    let ix = dao.initialize_dao_ix(DaoConfig {
+        min_votes: 1,
+        voting_period: 3600,
+        weighted_mints: vec![
+            WeightedMint { mint: lp_mint, weight: 3 },
+            WeightedMint { mint: bonus_mint, weight: 2 },
+        ],
+        ..DaoConfig::default()
+    });
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let action = ProposalAction::SetName { mint: dao.mint, name: "New Name".to_string() };
+    let ix = dao.new_polling_ix(action);
+    dao.process(&[ix], &[]).await.unwrap();
+    Fixture { dao, lp_token_account, bonus_token_account }
+}
+
+#[tokio::test]
+async fn combines_weighted_balances() {
+    let mut fixture = setup().await;
+    let ix = fixture.dao.vote_weighted_ix(true, &[fixture.lp_token_account, fixture.bonus_token_account]);
+    let logs = fixture.dao.process_with_logs(&[ix], &[]).await;
+
+    // 100 governing tokens, 50 LP tokens at 3x and 10 bonus tokens at 2x
+    let votes = decode_events::<VoteCast>(&logs);
+    assert_eq!(votes.len(), 1);
+    assert_eq!(votes[0].weight, 270);
+    let election: Election = fixture.dao.account(election_address(&fixture.dao.payer())).await;
+    assert_eq!(election.votes_for, 270);
+    assert_eq!(election.number_of_votes, 1);
+}
+
+#[tokio::test]
+async fn rejects_counting_a_mint_twice() {
+    let mut fixture = setup().await;
+    let ix = fixture.dao.vote_weighted_ix(true, &[fixture.lp_token_account, fixture.lp_token_account]);
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::DuplicateVotingMint);
+
+    let ix = fixture.dao.vote_weighted_ix(true, &[fixture.dao.token_account]);
+    assert_custom_error(fixture.dao.process(&[ix], &[]).await, CustomError::DuplicateVotingMint);
+}
+
+#[tokio::test]
+async fn checks_weighted_token_accounts() {
+    let mut fixture = setup().await;
+    let lp_mint = fixture.dao.token_account_state(fixture.lp_token_account).await.mint;
+    let other_owner = fixture.dao.create_token_account(lp_mint, Pubkey::new_unique(), 50);
+    let ix = fixture.dao.vote_weighted_ix(true, &[other_owner]);
+    assert_anchor_error(fixture.dao.process(&[ix], &[]).await, ErrorCode::ConstraintTokenOwner);
+
+    let (_, unlisted) = fixture.dao.create_mint(50);
+    let ix = fixture.dao.vote_weighted_ix(true, &[unlisted]);
+    assert_anchor_error(fixture.dao.process(&[ix], &[]).await, ErrorCode::ConstraintTokenMint);
+}
+
+#[tokio::test]
+async fn rejects_invalid_weighted_mints() {
+    let mut dao = start(program_test(), 100).await;
+    let (lp_mint, _) = dao.create_mint(50);
+    for weighted_mints in [
+        vec![WeightedMint { mint: lp_mint, weight: 0 }],
+        vec![WeightedMint { mint: lp_mint, weight: 1 }, WeightedMint { mint: lp_mint, weight: 2 }],
+    ] {
+        let ix = dao.initialize_dao_ix(DaoConfig { min_votes: 1, weighted_mints, ..DaoConfig::default() });
+        assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidDaoConfig);
+    }
+}
+
+#[tokio::test]
+async fn rejects_weighted_mints_with_transfer_restrictions() {
+    let mut dao = start(program_test(), 100).await;
+    for extension in [ExtensionType::TransferHook, ExtensionType::NonTransferable] {
+        let mint = dao.create_token_2022_mint(&[extension]).await;
+        let weighted_mints = vec![WeightedMint { mint, weight: 2 }];
+        let ix = dao.initialize_dao_ix(DaoConfig { min_votes: 1, weighted_mints, ..DaoConfig::default() });
+        assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidGoverningMint);
+    }
+
+    // The mint accounts have to be passed, in config order
+    let (lp_mint, _) = dao.create_mint(50);
+    let config = DaoConfig { min_votes: 1, weighted_mints: vec![WeightedMint { mint: lp_mint, weight: 2 }], ..DaoConfig::default() };
+    let mut ix = dao.initialize_dao_ix(config.clone());
+    ix.accounts.pop();
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidMintAccounts);
+    let ix = dao.initialize_dao_ix(config);
+    dao.process(&[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn checks_weighted_mints_set_by_proposal() {
+    let mut fixture = setup().await;
+    let dao = &mut fixture.dao;
+    // Defeat the open proposal and close it, so the next one gets a fresh id
+    let ixs = [dao.vote_ix(false), dao.sum_up_ix().await];
+    dao.process(&ixs, &[]).await.unwrap();
+    let ix = dao.close_election_ix(dao.payer());
+    dao.process(&[ix], &[]).await.unwrap();
+    let now = dao.now().await;
+    dao.set_clock(now + 1).await;
+
+    let hooked = dao.create_token_2022_mint(&[ExtensionType::TransferHook]).await;
+    let config = DaoConfig { min_votes: 1, weighted_mints: vec![WeightedMint { mint: hooked, weight: 2 }], ..DaoConfig::default() };
+    let action = ProposalAction::SetDaoConfig { config: config.clone() };
+    let ixs = [dao.new_polling_ix(action), dao.vote_ix(true), dao.sum_up_ix().await];
+    dao.process(&ixs, &[]).await.unwrap();
+
+    let ix = dao.execute_proposal_ix(vec![]).await;
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidMintAccounts);
+    let ix = dao.execute_proposal_ix(weighted_mint_metas(&config)).await;
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidGoverningMint);
+}
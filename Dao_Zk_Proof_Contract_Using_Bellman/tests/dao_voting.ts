@@ -14,10 +14,16 @@ describe("dao_voting", () => {
   let election: Keypair;
   let userStatePda: PublicKey;
   let electionPda: PublicKey;
-  let changableTokenAccountPda: PublicKey;
+  let daoPda: PublicKey;
+  let resultPda: PublicKey;
+  let treasuryPda: PublicKey;
   let electionBump: number;
   let userBump: number;
-  let changableTokenAccountBump: number;
+  let daoBump: number;
+
+  const token = new PublicKey("YourTokenPublicKey"); // Replace with actual token public key
+  const metadataMint = new PublicKey("YourToken2022MintPublicKey"); // Token-2022 mint whose metadata update authority is the governance PDA
+  const TOKEN_2022_PROGRAM_ID = new PublicKey("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
 
   before(async () => {
     election = Keypair.generate();
@@ -32,26 +38,64 @@ describe("dao_voting", () => {
       program.programId
     );
 
-    [changableTokenAccountPda, changableTokenAccountBump] = await PublicKey.findProgramAddress(
-      [Buffer.from("changabletoken"), provider.wallet.publicKey.toBuffer()],
+    [daoPda, daoBump] = await PublicKey.findProgramAddress(
+      [Buffer.from("dao"), token.toBuffer()],
+      program.programId
+    );
+
+    [treasuryPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("treasury"), daoPda.toBuffer()],
       program.programId
     );
   });
 
+  it("Initializes the DAO", async () => {
+    try {
+      const config = {
+        minVotes: new BN(1),
+        votingPeriod: new BN(60),
+        executionDelay: new BN(0),
+        guardian: provider.wallet.publicKey,
+        maxTransferPerProposal: new BN(1_000),
+        maxTransferPerEpoch: new BN(10_000),
+        proposalDeposit: new BN(0),
+        votingPower: { token: {} },
+        weightedMints: [], // e.g. [{ mint: lpMint, weight: new BN(2) }], with each mint passed as a remaining account
+      };
+
+      const tx = await program.methods
+        .initializeDao(token, config)
+        .accounts({
+          dao: daoPda,
+          mint: token,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      console.log("Initialize DAO transaction signature", tx);
+
+      const daoAccount = await program.account.dao.fetch(daoPda);
+      expect(daoAccount.token.toString()).to.equal(token.toString());
+      expect(daoAccount.config.minVotes.toNumber()).to.equal(1);
+    } catch (error) {
+      console.error("Error during DAO initialization:", error);
+      throw error;
+    }
+  });
+
   it("Creates a new polling", async () => {
     try {
-      const token = new PublicKey("YourTokenPublicKey"); // Replace with actual token public key
-      const proposalVoting = "newSymbol";
-      const value = "NEW";
-      const additionalValue = "Additional Value";
+      const action = { setSymbol: { mint: metadataMint, symbol: "NEW" } };
 
       const tx = await program.methods
-        .newPolling(token, proposalVoting, value, additionalValue)
+        .newPolling(action)
         .accounts({
           election: electionPda,
+          dao: daoPda,
           authority: provider.wallet.publicKey,
           tokenAccount: provider.wallet.publicKey, // Replace with actual token account
-          changableTokenAccount: changableTokenAccountPda, // Replace with actual changable token account
+          nftMint: null, // Only needed for NFT DAOs
           verifyingKey: Keypair.generate().publicKey, // Replace with actual verifying key account
           systemProgram: SystemProgram.programId,
         })
@@ -62,7 +106,8 @@ describe("dao_voting", () => {
 
       const electionAccount = await program.account.election.fetch(electionPda);
       expect(electionAccount.creator.toString()).to.equal(provider.wallet.publicKey.toString());
-      expect(electionAccount.proposalVoting).to.equal(proposalVoting);
+      expect(electionAccount.action).to.deep.equal(action);
+      expect(electionAccount.executed).to.be.false;
       expect(electionAccount.voteActive).to.be.true;
 
       console.log("Election State:", electionAccount);
@@ -82,7 +127,6 @@ describe("dao_voting", () => {
           election: electionPda,
           authority: provider.wallet.publicKey,
           tokenAccount: provider.wallet.publicKey, // Replace with actual token account
-          changableTokenAccount: changableTokenAccountPda, // Replace with actual changable token account
           user: userStatePda,
           verifyingKey: Keypair.generate().publicKey, // Replace with actual verifying key account
           systemProgram: SystemProgram.programId,
@@ -105,20 +149,38 @@ describe("dao_voting", () => {
 
   it("Summarizes the results", async () => {
     try {
+      let electionAccount = await program.account.election.fetch(electionPda);
+      [resultPda] = await PublicKey.findProgramAddress(
+        [
+          Buffer.from("result"),
+          provider.wallet.publicKey.toBuffer(),
+          electionAccount.id.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
       const tx = await program.methods
         .toSumUp()
         .accounts({
           election: electionPda,
+          dao: daoPda,
+          result: resultPda,
+          creator: provider.wallet.publicKey,
+          treasury: treasuryPda,
           authority: provider.wallet.publicKey,
-          tokenAccount: provider.wallet.publicKey, // Replace with actual token account
-          changableTokenAccount: changableTokenAccountPda, // Replace with actual changable token account
+          systemProgram: SystemProgram.programId,
         })
         .rpc();
 
       console.log("Summarize transaction signature", tx);
 
-      const electionAccount = await program.account.election.fetch(electionPda);
+      electionAccount = await program.account.election.fetch(electionPda);
       expect(electionAccount.voteActive).to.be.false;
+      expect(electionAccount.state).to.deep.equal({ succeeded: {} });
+
+      const resultAccount = await program.account.proposalResult.fetch(resultPda);
+      expect(resultAccount.state).to.deep.equal({ succeeded: {} });
+      expect(resultAccount.numberOfVotes.toNumber()).to.equal(1);
 
       console.log("Election State after summarizing:", electionAccount);
     } catch (error) {
@@ -127,6 +189,45 @@ describe("dao_voting", () => {
     }
   });
 
+  it("Executes the proposal", async () => {
+    try {
+      const [governancePda] = await PublicKey.findProgramAddress(
+        [Buffer.from("governance"), daoPda.toBuffer()],
+        program.programId
+      );
+
+      const tx = await program.methods
+        .executeProposal()
+        .accounts({
+          election: electionPda,
+          result: resultPda,
+          dao: daoPda,
+          governance: governancePda,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          // Only needed for treasury and mint actions
+          treasury: null,
+          spending: null,
+          treasuryTokenAccount: null,
+          recipient: null,
+          mint: null,
+          tokenProgram: null,
+          stream: null,
+          metadataMint,
+          token2022Program: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+
+      console.log("Execute proposal transaction signature", tx);
+
+      const electionAccount = await program.account.election.fetch(electionPda);
+      expect(electionAccount.executed).to.be.true;
+    } catch (error) {
+      console.error("Error during proposal execution:", error);
+      throw error;
+    }
+  });
+
   it("Gets results", async () => {
     try {
       const tx = await program.methods
@@ -149,12 +250,15 @@ describe("dao_voting", () => {
         .closeElection()
         .accounts({
           election: electionPda,
-          authority: provider.wallet.publicKey,
-          changableTokenAccount: changableTokenAccountPda, // Replace with actual changable token account
+          creator: provider.wallet.publicKey,
         })
         .rpc();
 
       console.log("Close election transaction signature", tx);
+
+      // The result record outlives the election
+      const resultAccount = await program.account.proposalResult.fetch(resultPda);
+      expect(resultAccount.state).to.deep.equal({ succeeded: {} });
     } catch (error) {
       console.error("Error during election closure:", error);
       throw error;
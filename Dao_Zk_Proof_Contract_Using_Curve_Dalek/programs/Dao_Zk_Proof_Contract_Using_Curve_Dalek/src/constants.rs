@@ -1,3 +1,5 @@
 pub const ELECTION_SEED: &str = "election";
 pub const CHANGABLE_TOKEN_SEED: &str = "changabletoken";
-pub const USER_SEED: &str = "user";
\ No newline at end of file
+pub const USER_SEED: &str = "user";
+pub const WEIGHT_COMMITMENT_SEED: &str = "weightcommitment";
+pub const MAX_OPTIONS: u8 = 4; // Larger option ballots outgrow a transaction
\ No newline at end of file
@@ -20,4 +20,22 @@ pub enum CustomError {
     ProofVerificationFailed,
     #[msg("Invalid Proof")]
     InvalidProof,
+    #[msg("Encryption key is not a valid non-identity Ristretto point.")]
+    InvalidEncryptionKey,
+    #[msg("The decrypted tally has not been published.")]
+    TallyNotPublished,
+    #[msg("The tally has already been published.")]
+    TallyAlreadyPublished,
+    #[msg("The election counts public weights.")]
+    WeightsNotHidden,
+    #[msg("The election hides weights and needs the voter's weight commitment.")]
+    MissingWeightCommitment,
+    #[msg("A multi-option poll takes between 2 and MAX_OPTIONS options.")]
+    InvalidOptionCount,
+    #[msg("The ballot or tally doesn't match the poll's options.")]
+    OptionMismatch,
+    #[msg("The voting period must be positive.")]
+    InvalidVotingPeriod,
+    #[msg("The voting period has ended.")]
+    VotingClosed,
 }
\ No newline at end of file
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+use crate::zk_proof::ElGamalCiphertext;
+
+#[event]
+pub struct ProposalCreated {
+    pub election: Pubkey,
+    pub id: u64,
+    pub creator: Pubkey,
+    pub token: Pubkey,
+    pub proposal_voting: String,
+    pub value: String,
+    pub additional_value: String,
+    pub voting_ends_at: i64,
+}
+
+#[event]
+pub struct VoteCast {
+    pub election: Pubkey,
+    pub voter: Pubkey,
+    pub ciphertext: ElGamalCiphertext,
+    pub weight: u64, // 0 when the election hides weights
+}
+
+#[event]
+pub struct OptionVoteCast {
+    pub election: Pubkey,
+    pub voter: Pubkey,
+    pub ciphertexts: Vec<ElGamalCiphertext>, // One per option
+    pub weight: u64, // 0 when the election hides weights
+}
+
+#[event]
+pub struct TallyPublished {
+    pub election: Pubkey,
+    pub id: u64,
+    pub yes: u64,
+    pub no: u64,
+    pub publisher: Pubkey,
+}
+
+#[event]
+pub struct OptionTallyPublished {
+    pub election: Pubkey,
+    pub id: u64,
+    pub counts: Vec<u64>, // Weighted votes per option
+    pub publisher: Pubkey,
+}
+
+#[event]
+pub struct ProposalFinalized {
+    pub election: Pubkey,
+    pub id: u64,
+    pub passed: bool,
+    pub current: i64,
+    pub number_of_votes: u64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub election: Pubkey,
+    pub id: u64,
+    pub executor: Pubkey,
+    pub proposal_voting: String,
+    pub value: String,
+}
+
+#[event]
+pub struct RewardGranted {
+    pub user: Pubkey,
+    pub election: Pubkey,
+    pub reward_points: u64, // Total after this grant
+}
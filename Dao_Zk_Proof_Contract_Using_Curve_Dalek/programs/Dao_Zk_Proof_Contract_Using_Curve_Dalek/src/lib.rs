@@ -1,80 +1,122 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::entrypoint::ProgramResult;
-use anchor_spl::token::{self, TokenAccount};
+use anchor_spl::token::TokenAccount;
 use solana_program::account_info::AccountInfo;
 use solana_program::program_pack::Pack;
+use solana_zk_token_sdk::zk_token_proof_program;
 use spl_token::state::Account as SplTokenAccount;
-use bellman::groth16::{Proof, prepare_verifying_key, verify_proof};
-use std::str::FromStr;
-use bellman::groth16::VerifyingKey as GrothVerifyingKey;
-use anchor_lang::error::AnchorError;
-use curve25519_dalek::scalar::Scalar;
-use rand::rngs::OsRng;
 
 pub mod zk_proof;
 pub mod constants;
 pub mod state;
 pub mod errors;
+pub mod events;
 
-use crate::{constants::*, state::*, errors::*, zk_proof::*};
+use crate::{constants::*, state::*, errors::*, events::*, zk_proof::*};
 
 declare_id!("3XuNmJEHjuk5Vo7U6fAPp1vJekyW2GJsSmWBWkLjnbyK");
 
 #[program]
 pub mod dao_voting {
-    use std::num::NonZeroI128;
-
     use super::*;
 
+    #[allow(clippy::too_many_arguments)] // Anchor takes instruction arguments as parameters
     pub fn new_polling(
         ctx: Context<NewPolling>,
         token: Pubkey,
         proposal_voting: String,
         value: String,
         additional_value: String,
+        encryption_key: ElGamalPubkey,
+        hidden_weights: bool,
+        options: u8,
+        voting_period: i64,
     ) -> Result<()> {
         let election = &mut ctx.accounts.election;
 
         require!(!election.vote_active, CustomError::VoteActive);
+        require!(validate_public_key(&encryption_key), CustomError::InvalidEncryptionKey);
+        require!(options == 0 || (2..=MAX_OPTIONS).contains(&options), CustomError::InvalidOptionCount);
+        require!(voting_period > 0, CustomError::InvalidVotingPeriod);
 
         let balance = get_token_balance(&ctx.accounts.token_account.to_account_info())?;
         require!(balance > 0, CustomError::InsufficientBalance);
 
-        election.id = Clock::get().unwrap().unix_timestamp as u64; // Unique identifier
+        let now = Clock::get().unwrap().unix_timestamp;
+        election.id = now as u64; // Unique identifier
         election.token = token;
         election.proposal_voting = proposal_voting;
         election.value = value;
         election.additional_value = additional_value;
         election.vote_active = true;
-        election.time = Clock::get().unwrap().unix_timestamp;
+        election.time = now.checked_add(voting_period).ok_or(CustomError::InvalidVotingPeriod)?;
         election.creator = ctx.accounts.authority.key();
+        election.encryption_key = encryption_key;
+        election.hidden_weights = hidden_weights;
+        election.encrypted_yes = ElGamalCiphertext::default();
+        election.encrypted_no = ElGamalCiphertext::default();
+        election.tally_published = false;
+        election.options = options;
+        election.encrypted_options = vec![ElGamalCiphertext::default(); usize::from(options)];
+
+        emit!(ProposalCreated {
+            election: election.key(),
+            id: election.id,
+            creator: election.creator,
+            token,
+            proposal_voting: election.proposal_voting.clone(),
+            value: election.value.clone(),
+            additional_value: election.additional_value.clone(),
+            voting_ends_at: election.time,
+        });
+        Ok(())
+    }
+
+    // Stores the voter's encrypted weight for a hidden-weight election. Kept apart from `vote` so
+    // neither transaction outgrows the size limit with its proofs.
+    pub fn commit_weight(ctx: Context<CommitWeight>, ciphertext: ElGamalCiphertext, proof: WeightProof) -> Result<()> {
+        let election = &ctx.accounts.election;
+        require!(election.vote_active, CustomError::VoteInactive);
+        require!(!election.tally_published, CustomError::TallyAlreadyPublished);
+        require!(Clock::get()?.unix_timestamp <= election.time, CustomError::VotingClosed);
+        require!(election.hidden_weights, CustomError::WeightsNotHidden);
+
+        let balance = get_token_balance(&ctx.accounts.token_account.to_account_info())?;
+        require!(balance > 0, CustomError::InsufficientBalance);
+        let context = ballot_context(election, ctx.accounts.authority.key());
+        let range_proof = ctx.accounts.range_proof.try_borrow_data()?;
+        require!(validate_weight_proof(&proof, &ciphertext, balance, &election.encryption_key, &context, &range_proof), CustomError::InvalidProof);
+
+        let weight_commitment = &mut ctx.accounts.weight_commitment;
+        weight_commitment.election = election.key();
+        weight_commitment.voter = ctx.accounts.authority.key();
+        weight_commitment.ciphertext = ciphertext;
+        weight_commitment.max_weight = balance;
         Ok(())
     }
 
-    pub fn vote(ctx: Context<Vote>, vote: bool) -> Result<()> {
+    // The ballot is encrypted and proven by the voter, so the plaintext vote never reaches the program
+    pub fn vote(ctx: Context<Vote>, ciphertext: ElGamalCiphertext, proof: VoteProof) -> Result<()> {
         let election = &mut ctx.accounts.election;
         require!(election.vote_active, CustomError::VoteInactive);
+        require!(!election.tally_published, CustomError::TallyAlreadyPublished);
+        require!(Clock::get()?.unix_timestamp <= election.time, CustomError::VotingClosed);
+        require!(election.options == 0, CustomError::OptionMismatch);
     
         let balance = get_token_balance(&ctx.accounts.token_account.to_account_info())?;
         require!(balance > 0, CustomError::InsufficientBalance);
         require!(!election.voters.contains(&ctx.accounts.authority.key()), CustomError::AlreadyVoted);
     
-        // Encrypt the vote
-        let public_key = ElGamalPubkey { compressed_point: [0; 32] }; // Use the correct public key
-        let ciphertext = zk_proof::elgamal_file::perform_encryption(vote, &public_key);
-    
-        // Generate the vote proof
-        let random_scalar = Scalar::random(&mut rand::rngs::OsRng);  // Use the randomness
-        let proof = create_vote_proof(vote, random_scalar, &public_key);
-    
-        // Verify the proof before adding it
-        require!(validate_vote_proof(&proof, &ciphertext, &public_key), CustomError::InvalidProof);
+        // The ballot must encrypt either 0 or exactly the voter's weight: their balance, or the committed one
+        let weight = voter_weight(election, ctx.accounts.weight_commitment.as_ref(), balance)?;
+        let context = ballot_context(election, ctx.accounts.authority.key());
+        require!(validate_committed_vote_proof(&proof, &ciphertext, &weight, &election.encryption_key, &context), CustomError::InvalidProof);
+
+        // A yes ballot adds the weight to the yes sum and a no ballot adds it to the no sum, without revealing which
+        let weighted_no = weight.checked_sub(&ciphertext).ok_or(CustomError::InvalidProof)?;
+        election.encrypted_yes = election.encrypted_yes.checked_add(&ciphertext).ok_or(CustomError::InvalidProof)?;
+        election.encrypted_no = election.encrypted_no.checked_add(&weighted_no).ok_or(CustomError::InvalidProof)?;
     
-        if vote {
-            election.current += balance as i64;
-        } else {
-            election.current -= balance as i64;
-        }
         election.number_of_votes += 1;
         election.voters.push(ctx.accounts.authority.key()); // Add voter to the list
 
@@ -82,25 +124,152 @@ pub mod dao_voting {
         let user = &mut ctx.accounts.user;
         user.pubkey = ctx.accounts.authority.key();
         user.reward_points += 1; // Award 1 reward point for voting
-        
+
+        emit!(VoteCast {
+            election: election.key(),
+            voter: ctx.accounts.authority.key(),
+            ciphertext,
+            weight: if election.hidden_weights { 0 } else { balance },
+        });
+        emit!(RewardGranted {
+            user: user.pubkey,
+            election: election.key(),
+            reward_points: user.reward_points,
+        });
+        Ok(())
+    }
+
+    // A multi-option ballot has one encrypted entry per option; the proof shows exactly one holds the weight
+    pub fn vote_options(ctx: Context<Vote>, ciphertexts: Vec<ElGamalCiphertext>, proof: OptionBallotProof) -> Result<()> {
+        let election = &mut ctx.accounts.election;
+        require!(election.vote_active, CustomError::VoteInactive);
+        require!(!election.tally_published, CustomError::TallyAlreadyPublished);
+        require!(Clock::get()?.unix_timestamp <= election.time, CustomError::VotingClosed);
+        require!(election.options > 0 && ciphertexts.len() == usize::from(election.options), CustomError::OptionMismatch);
+
+        let balance = get_token_balance(&ctx.accounts.token_account.to_account_info())?;
+        require!(balance > 0, CustomError::InsufficientBalance);
+        require!(!election.voters.contains(&ctx.accounts.authority.key()), CustomError::AlreadyVoted);
+
+        let weight = voter_weight(election, ctx.accounts.weight_commitment.as_ref(), balance)?;
+        let context = ballot_context(election, ctx.accounts.authority.key());
+        require!(validate_option_ballot_proof(&proof, &ciphertexts, &weight, &election.encryption_key, &context), CustomError::InvalidProof);
+
+        for (sum, ciphertext) in election.encrypted_options.iter_mut().zip(&ciphertexts) {
+            *sum = sum.checked_add(ciphertext).ok_or(CustomError::InvalidProof)?;
+        }
+
+        election.number_of_votes += 1;
+        election.voters.push(ctx.accounts.authority.key());
+
+        let user = &mut ctx.accounts.user;
+        user.pubkey = ctx.accounts.authority.key();
+        user.reward_points += 1;
+
+        emit!(OptionVoteCast {
+            election: election.key(),
+            voter: ctx.accounts.authority.key(),
+            ciphertexts,
+            weight: if election.hidden_weights { 0 } else { balance },
+        });
+        emit!(RewardGranted {
+            user: user.pubkey,
+            election: election.key(),
+            reward_points: user.reward_points,
+        });
+        Ok(())
+    }
+
+    // Whoever holds the election secret reveals the sums, proving each decrypts the on-chain aggregate
+    pub fn publish_tally(
+        ctx: Context<PublishTally>,
+        yes: u64,
+        no: u64,
+        yes_proof: DecryptionProof,
+        no_proof: DecryptionProof,
+    ) -> Result<()> {
+        let election = &mut ctx.accounts.election;
+        require!(election.vote_active, CustomError::VoteInactive);
+        require!(!election.tally_published, CustomError::TallyAlreadyPublished);
+        // The key holder can decrypt the running sums at any time, so publishing waits for the deadline
+        require!(Clock::get()?.unix_timestamp > election.time, CustomError::VotingTime);
+        require!(election.options == 0, CustomError::OptionMismatch);
+
+        require!(validate_decryption_proof(&yes_proof, &election.encrypted_yes, yes, &election.encryption_key), CustomError::InvalidProof);
+        require!(validate_decryption_proof(&no_proof, &election.encrypted_no, no, &election.encryption_key), CustomError::InvalidProof);
+
+        election.current = i64::try_from(i128::from(yes) - i128::from(no)).map_err(|_| CustomError::InvalidPublicInput)?;
+        election.tally_published = true;
+
+        emit!(TallyPublished {
+            election: election.key(),
+            id: election.id,
+            yes,
+            no,
+            publisher: ctx.accounts.authority.key(),
+        });
+        Ok(())
+    }
+
+    // The same for a multi-option poll, one count and proof per option. Such polls don't execute a
+    // change, so `current` stays 0.
+    pub fn publish_option_tally(ctx: Context<PublishTally>, counts: Vec<u64>, proofs: Vec<DecryptionProof>) -> Result<()> {
+        let election = &mut ctx.accounts.election;
+        require!(election.vote_active, CustomError::VoteInactive);
+        require!(!election.tally_published, CustomError::TallyAlreadyPublished);
+        require!(Clock::get()?.unix_timestamp > election.time, CustomError::VotingTime);
+        let options = usize::from(election.options);
+        require!(options > 0 && counts.len() == options && proofs.len() == options, CustomError::OptionMismatch);
+
+        for ((sum, count), proof) in election.encrypted_options.iter().zip(&counts).zip(&proofs) {
+            require!(validate_decryption_proof(proof, sum, *count, &election.encryption_key), CustomError::InvalidProof);
+        }
+        election.tally_published = true;
+
+        emit!(OptionTallyPublished {
+            election: election.key(),
+            id: election.id,
+            counts,
+            publisher: ctx.accounts.authority.key(),
+        });
         Ok(())
     }
-    
 
     pub fn to_sum_up(ctx: Context<ToSumUp>) -> Result<()> {
         let election = &mut ctx.accounts.election;
         require!(election.vote_active, CustomError::VoteInactive);
         require!(Clock::get().unwrap().unix_timestamp > election.time || election.number_of_votes >= election.min_votes, CustomError::VotingTime);
+        require!(election.tally_published, CustomError::TallyNotPublished);
 
-        if election.current > 0 {
+        let passed = election.current > 0;
+        let mut executed = false;
+        if passed {
             if election.proposal_voting == "newSymbol" {
                 ctx.accounts.changable_token_account.change_symbol(election.value.clone())?;
+                executed = true;
             }
             if election.proposal_voting == "newName" {
                 ctx.accounts.changable_token_account.change_name(election.value.clone())?;
+                executed = true;
             }
         }
 
+        emit!(ProposalFinalized {
+            election: election.key(),
+            id: election.id,
+            passed,
+            current: election.current,
+            number_of_votes: election.number_of_votes,
+        });
+        if executed {
+            emit!(ProposalExecuted {
+                election: election.key(),
+                id: election.id,
+                executor: ctx.accounts.authority.key(),
+                proposal_voting: election.proposal_voting.clone(),
+                value: election.value.clone(),
+            });
+        }
         Ok(())
     }
 
@@ -119,6 +288,11 @@ pub mod dao_voting {
         election.vote_active = false;
         election.current = 0;
         election.number_of_votes = 0;
+        election.encrypted_yes = ElGamalCiphertext::default();
+        election.encrypted_no = ElGamalCiphertext::default();
+        election.tally_published = false;
+        election.options = 0;
+        election.encrypted_options.clear();
         election.proposal_voting = String::new();
         election.value = String::new();
         election.additional_value = String::new();
@@ -139,7 +313,7 @@ pub struct NewPolling<'info> {
     #[account(
         init,
         payer = authority, 
-        space = 8 + 64 + 256 + 256 + 8 + 8 + 8 + 1 + 32 + 8 + 1024,
+        space = 8 + 64 + 256 + 256 + 8 + 8 + 8 + 1 + 32 + 8 + 32 + 1 + 64 + 64 + 1 + 1 + 4 + 64 * MAX_OPTIONS as usize + 1024,
         seeds = [ELECTION_SEED.as_bytes(), authority.key().as_ref()],
         bump
     )]
@@ -170,7 +344,8 @@ pub struct Vote<'info> {
     pub election: Account<'info, Election>,
     #[account(mut)]
     pub authority: Signer<'info>,
-    #[account(mut)]
+    // The voter's own balance of the election's token is the weight ballots are proven against
+    #[account(mut, token::mint = election.token, token::authority = authority)]
     pub token_account: Account<'info, TokenAccount>,
     #[account(
         mut,
@@ -186,10 +361,51 @@ pub struct Vote<'info> {
         bump
     )]
     pub user: Account<'info, User>,
+    #[account(
+        seeds = [WEIGHT_COMMITMENT_SEED.as_bytes(), election.key().as_ref(), &election.id.to_le_bytes(), authority.key().as_ref()],
+        bump,
+    )]
+    pub weight_commitment: Option<Account<'info, WeightCommitment>>, // Only for hidden-weight elections
     pub verifying_key: Account<'info, VerifyingKey>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct CommitWeight<'info> {
+    #[account(
+        seeds = [ELECTION_SEED.as_bytes(), election.creator.as_ref()],
+        bump,
+    )]
+    pub election: Account<'info, Election>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 32 + 64 + 8,
+        seeds = [WEIGHT_COMMITMENT_SEED.as_bytes(), election.key().as_ref(), &election.id.to_le_bytes(), authority.key().as_ref()],
+        bump
+    )]
+    pub weight_commitment: Account<'info, WeightCommitment>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(token::mint = election.token, token::authority = authority)]
+    pub token_account: Account<'info, TokenAccount>,
+    /// CHECK: Context state the ZK Token proof program wrote for the weight's range proof
+    #[account(owner = zk_token_proof_program::ID)]
+    pub range_proof: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PublishTally<'info> {
+    #[account(
+        mut,
+        seeds = [ELECTION_SEED.as_bytes(), election.creator.as_ref()],
+        bump,
+    )]
+    pub election: Account<'info, Election>,
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct ToSumUp<'info> {
     #[account(
@@ -272,3 +488,23 @@ fn get_token_balance(account: &AccountInfo) -> Result<u64> {
     let token_account = SplTokenAccount::unpack(data)?;
     Ok(token_account.amount)
 }
+
+// What ballot and weight proofs from `voter` are bound to
+fn ballot_context(election: &Account<Election>, voter: Pubkey) -> BallotContext {
+    BallotContext {
+        program_id: crate::ID,
+        election: election.key(),
+        election_id: election.id,
+        voter,
+    }
+}
+
+// The ciphertext a ballot's entries must add up to: the committed weight, or the public balance
+fn voter_weight(election: &Election, weight_commitment: Option<&Account<WeightCommitment>>, balance: u64) -> Result<ElGamalCiphertext> {
+    if !election.hidden_weights {
+        return Ok(ElGamalCiphertext::trivial(balance));
+    }
+    let weight_commitment = weight_commitment.ok_or(CustomError::MissingWeightCommitment)?;
+    require!(balance >= weight_commitment.max_weight, CustomError::InsufficientBalance);
+    Ok(weight_commitment.ciphertext.clone())
+}
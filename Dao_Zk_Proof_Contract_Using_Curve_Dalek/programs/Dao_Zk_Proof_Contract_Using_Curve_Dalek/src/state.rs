@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::zk_proof::{ElGamalCiphertext, ElGamalPubkey};
+
 #[account]
 pub struct Election {
     pub id: u64, // Unique identifier for the proposal
@@ -10,13 +12,29 @@ pub struct Election {
     pub current: i64, // Modified to i64 to accommodate negative votes
     pub number_of_votes: u64,
     pub vote_active: bool,
-    pub time: i64,
+    pub time: i64, // Voting deadline; ballots are taken until then and the tally published after
     pub min_votes: u64,
     pub count: u64,
     pub creator: Pubkey, // The address of the proposal creator
+    pub encryption_key: ElGamalPubkey, // Ballots are encrypted to this key and verified against it
+    pub hidden_weights: bool, // Voters commit to a weight up to their balance with `commit_weight` instead of voting their balance
+    pub encrypted_yes: ElGamalCiphertext, // Weighted sums of the ballots, readable only with the election secret
+    pub encrypted_no: ElGamalCiphertext,
+    pub tally_published: bool, // Set by `publish_tally` once the decrypted sums are proven; closes voting
+    pub options: u8, // 0 for a yes/no poll, otherwise the number of options voted on with `vote_options`
+    pub encrypted_options: Vec<ElGamalCiphertext>, // Weighted sum per option, in place of the yes/no sums
     pub voters: Vec<Pubkey>, // List of voters
 }
 
+// A voter's encrypted weight in a hidden-weight election, proven to lie in [0, max_weight]
+#[account]
+pub struct WeightCommitment {
+    pub election: Pubkey,
+    pub voter: Pubkey,
+    pub ciphertext: ElGamalCiphertext,
+    pub max_weight: u64, // Voter's balance when committing; `vote` requires at least as much
+}
+
 #[account]
 pub struct ChangableTokenAccount {
     pub name: String,
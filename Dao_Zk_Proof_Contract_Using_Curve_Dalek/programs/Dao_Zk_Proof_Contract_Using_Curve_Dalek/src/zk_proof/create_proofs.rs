@@ -1,87 +1,220 @@
-use super::elgamal_file::{ElGamalPubkey, ElGamalCiphertext};
+use super::curve::{sums_to_identity, IDENTITY};
+use super::elgamal_file::{decompress, encrypt_value, ElGamalPubkey, ElGamalCiphertext, GENERATOR_G, GENERATOR_G_BYTES, GENERATOR_H_BYTES};
+use super::transcript::{challenge_scalar, vote_transcript, BallotContext};
 use anchor_lang::prelude::*;
-use curve25519_dalek::ristretto::{RistrettoPoint, CompressedRistretto};
+use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
 use curve25519_dalek::traits::Identity;
 use merlin::Transcript;
 use rand::rngs::OsRng;
 
+// Cramer-Damgård-Schoenmakers proof that a ballot encrypts 0 or the voter's weight w. Branch j
+// shows that the ballot minus the j-th offset encrypts zero, i.e. is (r·G, r·P) for some r. The
+// offsets are the identity and the weight ciphertext: (0, w·H) for public weights, or the voter's
+// committed (b·G, b·P + w·H) when weights are hidden. The voter simulates the branch they can't
+// prove, and the two branch challenges must add up to the transcript challenge.
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct VoteProof {
-    pub proof_component1: [u8; 32],
-    pub proof_component2: [u8; 32],
+    pub commitment0_1: [u8; 32], // k·G and k·P for the plaintext 0 branch
+    pub commitment0_2: [u8; 32],
+    pub commitment1_1: [u8; 32], // The same for the plaintext w branch
+    pub commitment1_2: [u8; 32],
+    pub challenge0: [u8; 32], // The plaintext w branch gets the rest of the transcript challenge
+    pub response0: [u8; 32],
     pub response1: [u8; 32],
-    pub response2: [u8; 32],
 }
 
-pub fn create_vote_proof(vote: bool, random_scalar: Scalar, public_key: &ElGamalPubkey) -> VoteProof {
-    let generator_point = RistrettoPoint::default();
-    let decompressed_public_point = CompressedRistretto::from_slice(&public_key.compressed_point)
-        .unwrap()
-        .decompress()
-        .unwrap();
+pub fn create_vote_proof(vote: bool, weight: u64, random_scalar: Scalar, public_key: &ElGamalPubkey, context: &BallotContext) -> VoteProof {
+    let ciphertext = encrypt_value(if vote { weight } else { 0 }, &random_scalar, public_key);
+    create_ballot_proof(&ciphertext, vote, weight, random_scalar, public_key, context)
+}
 
-    let vote_scalar = if vote { Scalar::ONE } else { Scalar::ZERO };
+// Proves `ciphertext` was encrypted with `random_scalar` and holds `vote × weight`. If it holds
+// anything else, or is submitted with another weight or context, the proof doesn't verify.
+pub fn create_ballot_proof(
+    ciphertext: &ElGamalCiphertext,
+    vote: bool,
+    weight: u64,
+    random_scalar: Scalar,
+    public_key: &ElGamalPubkey,
+    context: &BallotContext,
+) -> VoteProof {
+    let weight = ElGamalCiphertext::trivial(weight);
+    create_committed_ballot_proof(ciphertext, vote, &weight, Scalar::ZERO, random_scalar, public_key, context)
+}
 
-    let random_response1 = Scalar::random(&mut OsRng);
-    let random_response2 = Scalar::random(&mut OsRng);
+// The same against a committed weight ciphertext made with randomness `weight_blinding`
+pub fn create_committed_ballot_proof(
+    ciphertext: &ElGamalCiphertext,
+    vote: bool,
+    weight: &ElGamalCiphertext,
+    weight_blinding: Scalar,
+    random_scalar: Scalar,
+    public_key: &ElGamalPubkey,
+    context: &BallotContext,
+) -> VoteProof {
+    let decompressed_public_point = decompress(&public_key.compressed_point).unwrap();
+    let (decompressed_component1, decompressed_component2) = ciphertext.points().unwrap();
+    let offsets = vote_offsets(weight).unwrap();
+    let known = usize::from(vote);
+    let simulated = 1 - known;
+    // The randomness of the ballot minus the known offset
+    let witness = if vote { random_scalar - weight_blinding } else { random_scalar };
 
-    let proof_component1_point = &generator_point * &random_response1;
-    let proof_component2_point = (&decompressed_public_point * &random_response1) + (&generator_point * &random_response2);
+    let mut commitments = [[RistrettoPoint::identity(); 2]; 2];
+    let mut challenges = [Scalar::ZERO; 2];
+    let mut responses = [Scalar::ZERO; 2];
 
-    let mut transcript = Transcript::new(b"vote_proof");
-    transcript.append_message(b"proof_component1", proof_component1_point.compress().as_bytes());
-    transcript.append_message(b"proof_component2", proof_component2_point.compress().as_bytes());
+    // Picking the challenge and response first lets the commitments be solved for
+    challenges[simulated] = Scalar::random(&mut OsRng);
+    responses[simulated] = Scalar::random(&mut OsRng);
+    let shifted_component1 = decompressed_component1 - offsets[simulated].0;
+    let shifted_component2 = decompressed_component2 - offsets[simulated].1;
+    commitments[simulated] = [
+        GENERATOR_G * responses[simulated] - shifted_component1 * challenges[simulated],
+        decompressed_public_point * responses[simulated] - shifted_component2 * challenges[simulated],
+    ];
 
-    let mut challenge_bytes = [0u8; 64];
-    transcript.challenge_bytes(b"challenge", &mut challenge_bytes);
-    let challenge_scalar = Scalar::from_bytes_mod_order_wide(&challenge_bytes);
+    let nonce = Scalar::random(&mut OsRng);
+    commitments[known] = [GENERATOR_G * nonce, decompressed_public_point * nonce];
 
-    let response1 = random_response1 + (challenge_scalar * random_scalar);
-    let response2 = random_response2 + (challenge_scalar * vote_scalar);
+    let commitments = commitments.map(|pair| pair.map(|point| point.compress().to_bytes()));
+    let challenge_scalar = vote_challenge(context, public_key, weight, ciphertext, &commitments);
+    challenges[known] = challenge_scalar - challenges[simulated];
+    responses[known] = nonce + challenges[known] * witness;
 
     VoteProof {
-        proof_component1: proof_component1_point.compress().to_bytes(),
-        proof_component2: proof_component2_point.compress().to_bytes(),
-        response1: response1.to_bytes(),
-        response2: response2.to_bytes(),
+        commitment0_1: commitments[0][0],
+        commitment0_2: commitments[0][1],
+        commitment1_1: commitments[1][0],
+        commitment1_2: commitments[1][1],
+        challenge0: challenges[0].to_bytes(),
+        response0: responses[0].to_bytes(),
+        response1: responses[1].to_bytes(),
+    }
+}
+
+pub fn validate_vote_proof(
+    proof: &VoteProof,
+    ciphertext: &ElGamalCiphertext,
+    weight: u64,
+    public_key: &ElGamalPubkey,
+    context: &BallotContext,
+) -> bool {
+    validate_committed_vote_proof(proof, ciphertext, &ElGamalCiphertext::trivial(weight), public_key, context)
+}
+
+// Malformed points or scalars from the client fail validation instead of panicking
+pub fn validate_committed_vote_proof(
+    proof: &VoteProof,
+    ciphertext: &ElGamalCiphertext,
+    weight: &ElGamalCiphertext,
+    public_key: &ElGamalPubkey,
+    context: &BallotContext,
+) -> bool {
+    let commitments = [
+        [proof.commitment0_1, proof.commitment0_2],
+        [proof.commitment1_1, proof.commitment1_2],
+    ];
+    let (Some(challenge0), Some(response0), Some(response1)) = (
+        Option::<Scalar>::from(Scalar::from_canonical_bytes(proof.challenge0)),
+        Option::<Scalar>::from(Scalar::from_canonical_bytes(proof.response0)),
+        Option::<Scalar>::from(Scalar::from_canonical_bytes(proof.response1)),
+    ) else {
+        return false;
+    };
+
+    let challenge_scalar = vote_challenge(context, public_key, weight, ciphertext, &commitments);
+    let challenges = [challenge0, challenge_scalar - challenge0];
+    let responses = [response0, response1];
+    let offsets = [[IDENTITY; 2], [weight.component1, weight.component2]];
+
+    // s·G = K1 + c·(C1 - offset1) and s·P = K2 + c·(C2 - offset2) for both branches. Invalid
+    // points, the key's included, fail the multiplication.
+    (0..2).all(|branch| {
+        let scalars = [responses[branch], -Scalar::ONE, -challenges[branch], challenges[branch]];
+        sums_to_identity(&scalars, &[GENERATOR_G_BYTES, commitments[branch][0], ciphertext.component1, offsets[branch][0]]) &&
+        sums_to_identity(&scalars, &[public_key.compressed_point, commitments[branch][1], ciphertext.component2, offsets[branch][1]])
+    })
+}
+
+// What a no and a yes ballot encrypt, as ciphertext points
+pub(crate) fn vote_offsets(weight: &ElGamalCiphertext) -> Option<[(RistrettoPoint, RistrettoPoint); 2]> {
+    Some([(RistrettoPoint::identity(), RistrettoPoint::identity()), weight.points()?])
+}
+
+fn vote_challenge(
+    context: &BallotContext,
+    public_key: &ElGamalPubkey,
+    weight: &ElGamalCiphertext,
+    ciphertext: &ElGamalCiphertext,
+    commitments: &[[[u8; 32]; 2]; 2],
+) -> Scalar {
+    let mut transcript = vote_transcript(context, public_key, weight, ciphertext);
+    for pair in commitments {
+        transcript.append_message(b"commitment1", &pair[0]);
+        transcript.append_message(b"commitment2", &pair[1]);
     }
+    challenge_scalar(&mut transcript)
+}
+
+// Chaum-Pedersen proof that `value` is the plaintext of a ciphertext (C1, C2), i.e. that the
+// election secret s satisfies both P = s·G and C2 - value·H = s·C1
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct DecryptionProof {
+    pub commitment1: [u8; 32], // k·G
+    pub commitment2: [u8; 32], // k·C1
+    pub response: [u8; 32],    // k + c·s
+}
+
+pub fn create_decryption_proof(ciphertext: &ElGamalCiphertext, value: u64, private_scalar: &Scalar) -> DecryptionProof {
+    let public_key = ElGamalPubkey {
+        compressed_point: (GENERATOR_G * private_scalar).compress().to_bytes(),
+    };
+    let decompressed_component1 = decompress(&ciphertext.component1).unwrap();
+
+    let nonce = Scalar::random(&mut OsRng);
+    let commitment1 = (GENERATOR_G * nonce).compress().to_bytes();
+    let commitment2 = (decompressed_component1 * nonce).compress().to_bytes();
+    let challenge_scalar = decryption_challenge(&public_key, ciphertext, value, &commitment1, &commitment2);
+
+    DecryptionProof {
+        commitment1,
+        commitment2,
+        response: (nonce + challenge_scalar * private_scalar).to_bytes(),
+    }
+}
+
+pub fn validate_decryption_proof(proof: &DecryptionProof, ciphertext: &ElGamalCiphertext, value: u64, public_key: &ElGamalPubkey) -> bool {
+    let Some(response_scalar) = Option::<Scalar>::from(Scalar::from_canonical_bytes(proof.response)) else {
+        return false;
+    };
+
+    let challenge_scalar = decryption_challenge(public_key, ciphertext, value, &proof.commitment1, &proof.commitment2);
+
+    // s·G = K1 + c·P and s·C1 = K2 + c·(C2 - value·H)
+    sums_to_identity(
+        &[response_scalar, -Scalar::ONE, -challenge_scalar],
+        &[GENERATOR_G_BYTES, proof.commitment1, public_key.compressed_point],
+    ) && sums_to_identity(
+        &[response_scalar, -Scalar::ONE, -challenge_scalar, challenge_scalar * Scalar::from(value)],
+        &[ciphertext.component1, proof.commitment2, ciphertext.component2, GENERATOR_H_BYTES],
+    )
 }
 
-pub fn validate_vote_proof(proof: &VoteProof, ciphertext: &ElGamalCiphertext, public_key: &ElGamalPubkey) -> bool {
-    let generator_point = RistrettoPoint::default();
-    let decompressed_public_point = CompressedRistretto::from_slice(&public_key.compressed_point)
-        .unwrap()
-        .decompress()
-        .unwrap();
-    let decompressed_component1 = CompressedRistretto::from_slice(&ciphertext.component1)
-        .unwrap()
-        .decompress()
-        .unwrap();
-    let decompressed_component2 = CompressedRistretto::from_slice(&ciphertext.component2)
-        .unwrap()
-        .decompress()
-        .unwrap();
-
-    let decompressed_proof_component1 = CompressedRistretto::from_slice(&proof.proof_component1)
-        .unwrap()
-        .decompress()
-        .unwrap();
-    let decompressed_proof_component2 = CompressedRistretto::from_slice(&proof.proof_component2)
-        .unwrap()
-        .decompress()
-        .unwrap();
-    let response1_scalar = Scalar::from_canonical_bytes(proof.response1).unwrap();
-    let response2_scalar = Scalar::from_canonical_bytes(proof.response2).unwrap();
-
-    let mut transcript = Transcript::new(b"vote_proof");
-    transcript.append_message(b"proof_component1", &proof.proof_component1);
-    transcript.append_message(b"proof_component2", &proof.proof_component2);
-
-    let mut challenge_bytes = [0u8; 64];
-    transcript.challenge_bytes(b"challenge", &mut challenge_bytes);
-    let challenge_scalar = Scalar::from_bytes_mod_order_wide(&challenge_bytes);
-
-    (&generator_point * &response1_scalar == decompressed_proof_component1 + (&decompressed_component1 * &challenge_scalar)) &&
-    ((&decompressed_public_point * &response1_scalar) + (&generator_point * &response2_scalar) == decompressed_proof_component2 + (&decompressed_component2 * &challenge_scalar))
+fn decryption_challenge(
+    public_key: &ElGamalPubkey,
+    ciphertext: &ElGamalCiphertext,
+    value: u64,
+    commitment1: &[u8; 32],
+    commitment2: &[u8; 32],
+) -> Scalar {
+    let mut transcript = Transcript::new(b"decryption_proof");
+    transcript.append_message(b"public_key", &public_key.compressed_point);
+    transcript.append_message(b"component1", &ciphertext.component1);
+    transcript.append_message(b"component2", &ciphertext.component2);
+    transcript.append_u64(b"value", value);
+    transcript.append_message(b"commitment1", commitment1);
+    transcript.append_message(b"commitment2", commitment2);
+    challenge_scalar(&mut transcript)
 }
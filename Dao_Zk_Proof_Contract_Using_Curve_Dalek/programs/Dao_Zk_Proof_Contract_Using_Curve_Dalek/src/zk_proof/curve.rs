@@ -0,0 +1,36 @@
+use curve25519_dalek::scalar::Scalar;
+use solana_zk_token_sdk::curve25519::ristretto::{self, PodRistrettoPoint};
+use solana_zk_token_sdk::curve25519::scalar::PodScalar;
+
+// Point arithmetic for the verifiers, on compressed points. On-chain it runs as Solana's curve25519
+// syscalls, which cost a few thousand compute units where dalek compiled to SBF spends hundreds of
+// thousands on a single decompression. Natively the SDK computes the same with dalek. Bytes that
+// aren't a valid point give `None`.
+pub const IDENTITY: [u8; 32] = [0; 32];
+
+pub(crate) fn is_valid_point(point: &[u8; 32]) -> bool {
+    ristretto::validate_ristretto(&PodRistrettoPoint(*point))
+}
+
+pub(crate) fn add_points(left: &[u8; 32], right: &[u8; 32]) -> Option<[u8; 32]> {
+    ristretto::add_ristretto(&PodRistrettoPoint(*left), &PodRistrettoPoint(*right)).map(|point| point.0)
+}
+
+pub(crate) fn subtract_points(left: &[u8; 32], right: &[u8; 32]) -> Option<[u8; 32]> {
+    ristretto::subtract_ristretto(&PodRistrettoPoint(*left), &PodRistrettoPoint(*right)).map(|point| point.0)
+}
+
+pub(crate) fn multiply_point(scalar: &Scalar, point: &[u8; 32]) -> Option<[u8; 32]> {
+    ristretto::multiply_ristretto(&PodScalar(scalar.to_bytes()), &PodRistrettoPoint(*point)).map(|point| point.0)
+}
+
+pub(crate) fn multiscalar_multiply(scalars: &[Scalar], points: &[[u8; 32]]) -> Option<[u8; 32]> {
+    let scalars: Vec<PodScalar> = scalars.iter().map(|scalar| PodScalar(scalar.to_bytes())).collect();
+    let points: Vec<PodRistrettoPoint> = points.iter().map(|point| PodRistrettoPoint(*point)).collect();
+    ristretto::multiscalar_multiply_ristretto(&scalars, &points).map(|point| point.0)
+}
+
+// Verification equations are rearranged to Σ scalar·point = 0, one multiscalar multiplication each
+pub(crate) fn sums_to_identity(scalars: &[Scalar], points: &[[u8; 32]]) -> bool {
+    multiscalar_multiply(scalars, points) == Some(IDENTITY)
+}
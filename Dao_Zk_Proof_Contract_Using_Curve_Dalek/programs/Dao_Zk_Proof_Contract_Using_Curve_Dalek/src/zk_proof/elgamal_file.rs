@@ -1,24 +1,87 @@
+use super::curve::{add_points, is_valid_point, multiply_point, subtract_points, IDENTITY};
 use anchor_lang::prelude::*;
+use curve25519_dalek::constants::{RISTRETTO_BASEPOINT_COMPRESSED, RISTRETTO_BASEPOINT_POINT};
 use curve25519_dalek::ristretto::{RistrettoPoint, CompressedRistretto};
 use curve25519_dalek::scalar::Scalar;
 use curve25519_dalek::traits::Identity;
 use rand::rngs::OsRng;
 use sha3::{Sha3_512, Digest};
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+// Keys and encryption randomness live on G, the vote on H. H is hashed to the curve from a fixed
+// label, so nobody knows its discrete log with respect to G. The program only uses H compressed,
+// precomputed so it never hashes to the curve.
+pub const GENERATOR_G: RistrettoPoint = RISTRETTO_BASEPOINT_POINT;
+pub const GENERATOR_G_BYTES: [u8; 32] = RISTRETTO_BASEPOINT_COMPRESSED.0;
+pub const GENERATOR_H_LABEL: &[u8] = b"Dao_Zk_Proof_Contract_Using_Curve_Dalek/elgamal/H";
+pub const GENERATOR_H_BYTES: [u8; 32] = [
+    124, 167, 112, 46, 166, 118, 240, 39, 155, 73, 83, 157, 183, 253, 75, 119,
+    254, 202, 33, 154, 213, 255, 12, 36, 184, 225, 247, 98, 87, 209, 59, 48,
+];
+
+pub fn generator_h() -> RistrettoPoint {
+    decompress(&GENERATOR_H_BYTES).unwrap()
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
 pub struct ElGamalPubkey {
     pub compressed_point: [u8; 32],
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+// (r·G, r·P + vote·H) for the election key P. The default is the identity pair, an encryption of 0.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
 pub struct ElGamalCiphertext {
     pub component1: [u8; 32],
     pub component2: [u8; 32],
 }
 
+// Componentwise arithmetic adds and scales the plaintexts under the same key.
+// Each returns `None` if a component isn't a valid point.
+impl ElGamalCiphertext {
+    // (0, value·H) encrypts `value` with no randomness, so anyone can build it
+    pub fn trivial(value: u64) -> Self {
+        Self {
+            component1: IDENTITY,
+            component2: multiply_point(&Scalar::from(value), &GENERATOR_H_BYTES).unwrap(),
+        }
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(Self {
+            component1: add_points(&self.component1, &other.component1)?,
+            component2: add_points(&self.component2, &other.component2)?,
+        })
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        Some(Self {
+            component1: subtract_points(&self.component1, &other.component1)?,
+            component2: subtract_points(&self.component2, &other.component2)?,
+        })
+    }
+
+    pub fn checked_mul(&self, factor: u64) -> Option<Self> {
+        let factor = Scalar::from(factor);
+        Some(Self {
+            component1: multiply_point(&factor, &self.component1)?,
+            component2: multiply_point(&factor, &self.component2)?,
+        })
+    }
+
+    pub(crate) fn points(&self) -> Option<(RistrettoPoint, RistrettoPoint)> {
+        Some((decompress(&self.component1)?, decompress(&self.component2)?))
+    }
+
+    fn from_points(component1: RistrettoPoint, component2: RistrettoPoint) -> Self {
+        Self {
+            component1: component1.compress().to_bytes(),
+            component2: component2.compress().to_bytes(),
+        }
+    }
+}
+
 pub fn create_keypair() -> (ElGamalPubkey, Scalar) {
     let secret_scalar = Scalar::random(&mut OsRng);
-    let public_point = &RistrettoPoint::default() * &secret_scalar;
+    let public_point = GENERATOR_G * secret_scalar;
     (
         ElGamalPubkey {
             compressed_point: public_point.compress().to_bytes(),
@@ -28,34 +91,53 @@ pub fn create_keypair() -> (ElGamalPubkey, Scalar) {
 }
 
 pub fn perform_encryption(vote: bool, public_key: &ElGamalPubkey) -> ElGamalCiphertext {
-    let random_scalar = Scalar::random(&mut OsRng);
-    let decompressed_public_point = CompressedRistretto::from_slice(&public_key.compressed_point)
-        .unwrap()
-        .decompress()
-        .unwrap();
-    let component1 = (&RistrettoPoint::default() * &random_scalar).compress();
-    let mut component2 = (&decompressed_public_point * &random_scalar).compress();
-    if vote {
-        component2 = (component2.decompress().unwrap() + RistrettoPoint::default()).compress();
-    }
-    ElGamalCiphertext {
-        component1: component1.to_bytes(),
-        component2: component2.to_bytes(),
-    }
+    encrypt_with_randomness(vote, &Scalar::random(&mut OsRng), public_key)
+}
+
+// Voters keep `random_scalar` to prove what the ciphertext holds with `create_vote_proof`
+pub fn encrypt_with_randomness(vote: bool, random_scalar: &Scalar, public_key: &ElGamalPubkey) -> ElGamalCiphertext {
+    encrypt_value(u64::from(vote), random_scalar, public_key)
+}
+
+// A weighted ballot encrypts `vote × weight`
+pub fn encrypt_value(value: u64, random_scalar: &Scalar, public_key: &ElGamalPubkey) -> ElGamalCiphertext {
+    let decompressed_public_point = decompress(&public_key.compressed_point).unwrap();
+    ElGamalCiphertext::from_points(
+        GENERATOR_G * random_scalar,
+        decompressed_public_point * random_scalar + generator_h() * Scalar::from(value),
+    )
 }
 
 pub fn perform_decryption(ciphertext: &ElGamalCiphertext, private_scalar: &Scalar) -> bool {
-    let decompressed_component1 = CompressedRistretto::from_slice(&ciphertext.component1)
-        .unwrap()
-        .decompress()
-        .unwrap();
-    let decompressed_component2 = CompressedRistretto::from_slice(&ciphertext.component2)
-        .unwrap()
-        .decompress()
-        .unwrap();
-    let shared_secret = &decompressed_component1 * private_scalar;
+    let decompressed_component1 = decompress(&ciphertext.component1).unwrap();
+    let decompressed_component2 = decompress(&ciphertext.component2).unwrap();
+    let shared_secret = decompressed_component1 * private_scalar;
     let message_point = decompressed_component2 - shared_secret;
-    message_point == RistrettoPoint::default()
+    message_point == generator_h()
+}
+
+// Recovers a tally sum by searching for value·H, so it is only practical for totals up to `max`
+pub fn decrypt_tally(ciphertext: &ElGamalCiphertext, private_scalar: &Scalar, max: u64) -> Option<u64> {
+    let (component1, component2) = ciphertext.points()?;
+    let message_point = component2 - component1 * private_scalar;
+    let generator = generator_h();
+    let mut candidate = RistrettoPoint::identity();
+    for value in 0..=max {
+        if candidate == message_point {
+            return Some(value);
+        }
+        candidate += generator;
+    }
+    None
+}
+
+// The identity point would make every ciphertext decryptable without the secret
+pub fn validate_public_key(public_key: &ElGamalPubkey) -> bool {
+    is_valid_point(&public_key.compressed_point) && public_key.compressed_point != IDENTITY
+}
+
+pub(crate) fn decompress(bytes: &[u8; 32]) -> Option<RistrettoPoint> {
+    CompressedRistretto::from_slice(bytes).ok()?.decompress()
 }
 
 pub fn create_ballot_hash(proposal_id: u64, user_pubkey: Pubkey) -> [u8; 64] {
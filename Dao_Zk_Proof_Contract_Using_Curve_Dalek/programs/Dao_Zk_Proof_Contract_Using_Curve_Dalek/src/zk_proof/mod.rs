@@ -1,5 +1,15 @@
+pub mod curve;
 pub mod elgamal_file;
 pub mod create_proofs;
+pub mod transcript;
+pub mod range_proof;
+pub mod weight_proof;
+pub mod option_ballot;
 
+pub use curve::IDENTITY;
 pub use elgamal_file::*;
-pub use create_proofs::*;
\ No newline at end of file
+pub use create_proofs::*;
+pub use transcript::*;
+pub use range_proof::*;
+pub use weight_proof::*;
+pub use option_ballot::*;
\ No newline at end of file
@@ -0,0 +1,190 @@
+use super::create_proofs::vote_offsets;
+use super::curve::{multiscalar_multiply, IDENTITY};
+use super::elgamal_file::{decompress, encrypt_value, ElGamalCiphertext, ElGamalPubkey, GENERATOR_G, GENERATOR_G_BYTES};
+use super::transcript::{challenge_scalar, option_ballot_transcript, BallotContext};
+use anchor_lang::prelude::*;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use merlin::Transcript;
+use rand::rngs::OsRng;
+
+// A multi-option ballot encrypts one entry per option. Each entry gets the CDS proof of a yes/no
+// ballot that it holds 0 or the voter's weight, and a sum proof shows that the entries minus the
+// weight ciphertext encrypt zero, so exactly one option gets the weight. All of them answer one
+// transcript challenge, and only the challenges and responses are sent: the verifier solves for
+// the commitments and checks they hash back to `challenge`. That keeps a ballot in a packet.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct OptionBallotProof {
+    pub challenge: [u8; 32],
+    pub entries: Vec<OptionEntryProof>,
+    pub sum_response: [u8; 32], // k + c·(Σ r_i - weight blinding)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct OptionEntryProof {
+    pub challenge0: [u8; 32], // The plaintext w branch gets the rest of the ballot challenge
+    pub response0: [u8; 32],
+    pub response1: [u8; 32],
+}
+
+// Encrypts the weight into entry `choice` and 0 into the others, with the proof
+pub fn create_option_ballot(
+    choice: usize,
+    options: usize,
+    weight: u64,
+    weight_ciphertext: &ElGamalCiphertext,
+    weight_blinding: Scalar,
+    public_key: &ElGamalPubkey,
+    context: &BallotContext,
+) -> (Vec<ElGamalCiphertext>, OptionBallotProof) {
+    let votes: Vec<bool> = (0..options).map(|option| option == choice).collect();
+    let random_scalars: Vec<Scalar> = (0..options).map(|_| Scalar::random(&mut OsRng)).collect();
+    let ciphertexts: Vec<ElGamalCiphertext> = votes
+        .iter()
+        .zip(&random_scalars)
+        .map(|(vote, random_scalar)| encrypt_value(if *vote { weight } else { 0 }, random_scalar, public_key))
+        .collect();
+    let proof = create_option_ballot_proof(&ciphertexts, &votes, weight_ciphertext, weight_blinding, &random_scalars, public_key, context);
+    (ciphertexts, proof)
+}
+
+// Proves entry i was encrypted with `random_scalars[i]` and holds `votes[i] × weight`. Public
+// weights are passed as `ElGamalCiphertext::trivial` with a zero blinding. Unless exactly one vote
+// is set, the sum proof doesn't verify.
+pub fn create_option_ballot_proof(
+    ciphertexts: &[ElGamalCiphertext],
+    votes: &[bool],
+    weight: &ElGamalCiphertext,
+    weight_blinding: Scalar,
+    random_scalars: &[Scalar],
+    public_key: &ElGamalPubkey,
+    context: &BallotContext,
+) -> OptionBallotProof {
+    let decompressed_public_point = decompress(&public_key.compressed_point).unwrap();
+    let offsets = vote_offsets(weight).unwrap();
+    let mut transcript = option_ballot_transcript(context, public_key, weight, ciphertexts);
+
+    // Every entry simulates the branch it can't prove, as in `create_committed_ballot_proof`
+    let mut entries = Vec::with_capacity(ciphertexts.len());
+    for ((ciphertext, vote), random_scalar) in ciphertexts.iter().zip(votes).zip(random_scalars) {
+        let (decompressed_component1, decompressed_component2) = ciphertext.points().unwrap();
+        let known = usize::from(*vote);
+        let simulated = 1 - known;
+        let witness = if *vote { random_scalar - weight_blinding } else { *random_scalar };
+
+        let mut commitments = [[RistrettoPoint::identity(); 2]; 2];
+        let simulated_challenge = Scalar::random(&mut OsRng);
+        let simulated_response = Scalar::random(&mut OsRng);
+        commitments[simulated] = [
+            GENERATOR_G * simulated_response - (decompressed_component1 - offsets[simulated].0) * simulated_challenge,
+            decompressed_public_point * simulated_response - (decompressed_component2 - offsets[simulated].1) * simulated_challenge,
+        ];
+        let nonce = Scalar::random(&mut OsRng);
+        commitments[known] = [GENERATOR_G * nonce, decompressed_public_point * nonce];
+        append_entry_commitments(&mut transcript, &commitments.map(|pair| pair.map(|point| point.compress().to_bytes())));
+        entries.push((known, witness, nonce, simulated_challenge, simulated_response));
+    }
+
+    // The entries minus the weight encrypt zero under Σ r_i - weight blinding
+    let sum_witness = random_scalars.iter().sum::<Scalar>() - weight_blinding;
+    let sum_nonce = Scalar::random(&mut OsRng);
+    let sum_commitments = [GENERATOR_G * sum_nonce, decompressed_public_point * sum_nonce];
+    append_sum_commitments(&mut transcript, &sum_commitments.map(|point| point.compress().to_bytes()));
+    let challenge = challenge_scalar(&mut transcript);
+
+    let entries = entries
+        .into_iter()
+        .map(|(known, witness, nonce, simulated_challenge, simulated_response)| {
+            let known_challenge = challenge - simulated_challenge;
+            let known_response = nonce + known_challenge * witness;
+            let (challenge0, response0, response1) = if known == 0 {
+                (known_challenge, known_response, simulated_response)
+            } else {
+                (simulated_challenge, simulated_response, known_response)
+            };
+            OptionEntryProof {
+                challenge0: challenge0.to_bytes(),
+                response0: response0.to_bytes(),
+                response1: response1.to_bytes(),
+            }
+        })
+        .collect();
+
+    OptionBallotProof {
+        challenge: challenge.to_bytes(),
+        entries,
+        sum_response: (sum_nonce + challenge * sum_witness).to_bytes(),
+    }
+}
+
+// Malformed points or scalars, or a proof for another number of entries, fail validation instead of panicking
+pub fn validate_option_ballot_proof(
+    proof: &OptionBallotProof,
+    ciphertexts: &[ElGamalCiphertext],
+    weight: &ElGamalCiphertext,
+    public_key: &ElGamalPubkey,
+    context: &BallotContext,
+) -> bool {
+    if ciphertexts.is_empty() || proof.entries.len() != ciphertexts.len() {
+        return false;
+    }
+    let (Some(challenge), Some(sum_response)) = (canonical(&proof.challenge), canonical(&proof.sum_response)) else {
+        return false;
+    };
+    let offsets = [[IDENTITY; 2], [weight.component1, weight.component2]];
+
+    // K = s·G - c·(C1 - offset1) and s·P - c·(C2 - offset2) per branch, and the sum commitments
+    // s·G - c·(Σ C1 - W1) and s·P - c·(Σ C2 - W2), each a multiscalar multiplication
+    let mut transcript = option_ballot_transcript(context, public_key, weight, ciphertexts);
+    let mut sum_scalars = vec![sum_response, challenge];
+    let mut sum_points = [vec![GENERATOR_G_BYTES, weight.component1], vec![public_key.compressed_point, weight.component2]];
+    for (entry, ciphertext) in proof.entries.iter().zip(ciphertexts) {
+        let (Some(challenge0), Some(response0), Some(response1)) =
+            (canonical(&entry.challenge0), canonical(&entry.response0), canonical(&entry.response1))
+        else {
+            return false;
+        };
+        let challenges = [challenge0, challenge - challenge0];
+        let responses = [response0, response1];
+        let mut commitments = [[IDENTITY; 2]; 2];
+        for branch in 0..2 {
+            let scalars = [responses[branch], -challenges[branch], challenges[branch]];
+            let (Some(commitment1), Some(commitment2)) = (
+                multiscalar_multiply(&scalars, &[GENERATOR_G_BYTES, ciphertext.component1, offsets[branch][0]]),
+                multiscalar_multiply(&scalars, &[public_key.compressed_point, ciphertext.component2, offsets[branch][1]]),
+            ) else {
+                return false;
+            };
+            commitments[branch] = [commitment1, commitment2];
+        }
+        append_entry_commitments(&mut transcript, &commitments);
+        sum_scalars.push(-challenge);
+        sum_points[0].push(ciphertext.component1);
+        sum_points[1].push(ciphertext.component2);
+    }
+    let (Some(sum_commitment1), Some(sum_commitment2)) = (
+        multiscalar_multiply(&sum_scalars, &sum_points[0]),
+        multiscalar_multiply(&sum_scalars, &sum_points[1]),
+    ) else {
+        return false;
+    };
+    append_sum_commitments(&mut transcript, &[sum_commitment1, sum_commitment2]);
+    challenge_scalar(&mut transcript) == challenge
+}
+
+fn append_entry_commitments(transcript: &mut Transcript, commitments: &[[[u8; 32]; 2]; 2]) {
+    for pair in commitments {
+        transcript.append_message(b"commitment1", &pair[0]);
+        transcript.append_message(b"commitment2", &pair[1]);
+    }
+}
+
+fn append_sum_commitments(transcript: &mut Transcript, commitments: &[[u8; 32]; 2]) {
+    transcript.append_message(b"sum_commitment1", &commitments[0]);
+    transcript.append_message(b"sum_commitment2", &commitments[1]);
+}
+
+fn canonical(bytes: &[u8; 32]) -> Option<Scalar> {
+    Scalar::from_canonical_bytes(*bytes).into()
+}
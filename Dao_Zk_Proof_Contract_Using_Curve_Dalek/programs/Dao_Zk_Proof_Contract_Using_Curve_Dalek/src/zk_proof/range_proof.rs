@@ -0,0 +1,50 @@
+use solana_zk_token_sdk::instruction::{BatchedRangeProofContext, ProofType};
+use solana_zk_token_sdk::zk_token_proof_state::ProofContextState;
+#[cfg(not(target_os = "solana"))]
+use {
+    curve25519_dalek::scalar::Scalar,
+    solana_zk_token_sdk::encryption::pedersen::{Pedersen, PedersenOpening},
+    solana_zk_token_sdk::instruction::BatchedRangeProofU128Data,
+};
+
+// Range proofs are aggregated Bulletproofs checked by Solana's native ZK Token proof program, for a
+// fixed 200k compute units; verifying one inside the program doesn't fit the transaction budget.
+// The voter verifies a `BatchedRangeProofU128Data` into a context state account, which the proof
+// program only writes once the proof holds, and the program reads the proven commitments back.
+// They are the proof program's Pedersen commitments v·G + r·H_p, with H_p hashed to the curve from G.
+pub const RANGE_BITS: u8 = 64;
+pub const PEDERSEN_H_BYTES: [u8; 32] = [
+    140, 146, 64, 180, 86, 169, 230, 220, 101, 195, 119, 161, 4, 141, 116, 95,
+    148, 160, 140, 219, 127, 68, 203, 205, 123, 70, 243, 64, 72, 135, 17, 52,
+];
+
+pub type RangeProofContextState = ProofContextState<BatchedRangeProofContext>;
+pub const RANGE_PROOF_CONTEXT_LEN: usize = std::mem::size_of::<RangeProofContextState>();
+
+// Proves both values lie in [0, 2^RANGE_BITS), committed with the given openings
+#[cfg(not(target_os = "solana"))]
+pub fn prove_range(values: [u64; 2], openings: [Scalar; 2]) -> BatchedRangeProofU128Data {
+    let openings = openings.map(|opening| PedersenOpening::from_bytes(opening.as_bytes()).unwrap());
+    let commitments = [0, 1].map(|i| Pedersen::with(values[i], &openings[i]));
+    BatchedRangeProofU128Data::new(
+        commitments.iter().collect(),
+        values.to_vec(),
+        vec![usize::from(RANGE_BITS); 2],
+        openings.iter().collect(),
+    )
+    .unwrap()
+}
+
+// Whether `context_state` is the data of a context state account that recorded a range proof for
+// exactly these commitments. The caller checks the account is owned by the proof program.
+pub(crate) fn proven_in_range(context_state: &[u8], commitments: &[[u8; 32]; 2]) -> bool {
+    let Ok(state) = RangeProofContextState::try_from_bytes(context_state) else {
+        return false;
+    };
+    let context = &state.proof_context;
+    state.proof_type == ProofType::BatchedRangeProofU128.into()
+        && commitments
+            .iter()
+            .zip(context.commitments.iter().zip(&context.bit_lengths))
+            .all(|(expected, (commitment, bits))| commitment.0 == *expected && *bits == RANGE_BITS)
+}
@@ -0,0 +1,87 @@
+use super::elgamal_file::{ElGamalCiphertext, ElGamalPubkey};
+use anchor_lang::prelude::*;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+// Fiat-Shamir protocols for ballot and weight proofs. Prover and verifier absorb the same fields
+// in the same order, so a proof only verifies for the ballot it was made for. Bump a version
+// whenever its absorbed fields change.
+pub const VOTE_PROOF_PROTOCOL: &[u8] = b"Dao_Zk_Proof_Contract_Using_Curve_Dalek/vote_proof";
+pub const VOTE_PROOF_VERSION: u64 = 3;
+pub const WEIGHT_PROOF_PROTOCOL: &[u8] = b"Dao_Zk_Proof_Contract_Using_Curve_Dalek/weight_proof";
+pub const WEIGHT_PROOF_VERSION: u64 = 2;
+pub const OPTION_BALLOT_PROTOCOL: &[u8] = b"Dao_Zk_Proof_Contract_Using_Curve_Dalek/option_ballot";
+pub const OPTION_BALLOT_VERSION: u64 = 1;
+
+// Who casts a ballot, and in which election, besides what the ciphertext holds
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BallotContext {
+    pub program_id: Pubkey,
+    pub election: Pubkey,
+    pub election_id: u64,
+    pub voter: Pubkey,
+}
+
+// Absorbs everything before the commitments, which the caller appends in a fixed order. Public
+// weights are passed as the trivial encryption (0, w·H).
+pub fn vote_transcript(
+    context: &BallotContext,
+    public_key: &ElGamalPubkey,
+    weight: &ElGamalCiphertext,
+    ciphertext: &ElGamalCiphertext,
+) -> Transcript {
+    let mut transcript = context_transcript(VOTE_PROOF_PROTOCOL, VOTE_PROOF_VERSION, context, public_key);
+    transcript.append_message(b"weight_component1", &weight.component1);
+    transcript.append_message(b"weight_component2", &weight.component2);
+    transcript.append_message(b"component1", &ciphertext.component1);
+    transcript.append_message(b"component2", &ciphertext.component2);
+    transcript
+}
+
+pub fn weight_transcript(
+    context: &BallotContext,
+    public_key: &ElGamalPubkey,
+    max_weight: u64,
+    weight: &ElGamalCiphertext,
+) -> Transcript {
+    let mut transcript = context_transcript(WEIGHT_PROOF_PROTOCOL, WEIGHT_PROOF_VERSION, context, public_key);
+    transcript.append_u64(b"max_weight", max_weight);
+    transcript.append_message(b"component1", &weight.component1);
+    transcript.append_message(b"component2", &weight.component2);
+    transcript
+}
+
+// Absorbs every entry in order, so entries can't be moved between options or ballots
+pub fn option_ballot_transcript(
+    context: &BallotContext,
+    public_key: &ElGamalPubkey,
+    weight: &ElGamalCiphertext,
+    ciphertexts: &[ElGamalCiphertext],
+) -> Transcript {
+    let mut transcript = context_transcript(OPTION_BALLOT_PROTOCOL, OPTION_BALLOT_VERSION, context, public_key);
+    transcript.append_message(b"weight_component1", &weight.component1);
+    transcript.append_message(b"weight_component2", &weight.component2);
+    transcript.append_u64(b"options", ciphertexts.len() as u64);
+    for ciphertext in ciphertexts {
+        transcript.append_message(b"component1", &ciphertext.component1);
+        transcript.append_message(b"component2", &ciphertext.component2);
+    }
+    transcript
+}
+
+fn context_transcript(protocol: &'static [u8], version: u64, context: &BallotContext, public_key: &ElGamalPubkey) -> Transcript {
+    let mut transcript = Transcript::new(protocol);
+    transcript.append_u64(b"version", version);
+    transcript.append_message(b"program_id", context.program_id.as_ref());
+    transcript.append_message(b"election", context.election.as_ref());
+    transcript.append_u64(b"election_id", context.election_id);
+    transcript.append_message(b"voter", context.voter.as_ref());
+    transcript.append_message(b"public_key", &public_key.compressed_point);
+    transcript
+}
+
+pub fn challenge_scalar(transcript: &mut Transcript) -> Scalar {
+    let mut challenge_bytes = [0u8; 64];
+    transcript.challenge_bytes(b"challenge", &mut challenge_bytes);
+    Scalar::from_bytes_mod_order_wide(&challenge_bytes)
+}
@@ -0,0 +1,119 @@
+use super::curve::multiscalar_multiply;
+use super::elgamal_file::{ElGamalCiphertext, ElGamalPubkey, GENERATOR_G_BYTES, GENERATOR_H_BYTES};
+use super::range_proof::{proven_in_range, PEDERSEN_H_BYTES};
+use super::transcript::{challenge_scalar, weight_transcript, BallotContext};
+use anchor_lang::prelude::*;
+use curve25519_dalek::scalar::Scalar;
+#[cfg(not(target_os = "solana"))]
+use {
+    super::elgamal_file::{decompress, encrypt_value, generator_h, GENERATOR_G},
+    super::range_proof::prove_range,
+    rand::rngs::OsRng,
+    solana_zk_token_sdk::instruction::BatchedRangeProofU128Data,
+};
+
+// Proves a weight ciphertext (b·G, b·P + w·H) is well formed and that 0 ≤ w ≤ `max_weight`. The
+// range proof is over the proof program's Pedersen commitments, so the voter also commits to
+// C = w·G + o·H_p and proves it holds the ciphertext's w. The range proof then covers C and
+// max_weight·G - C, which commits to max_weight - w. The sigma proof sends its challenge instead
+// of the commitments k_b·G, k_w·H + k_b·P and k_w·G + k_o·H_p to keep the transaction small.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct WeightProof {
+    pub commitment: [u8; 32], // C
+    pub challenge: [u8; 32],
+    pub response_weight: [u8; 32],
+    pub response_blinding: [u8; 32],
+    pub response_opening: [u8; 32],
+}
+
+// Encrypts `weight` for the voter's weight commitment. The voter verifies the returned range proof
+// with the proof program before calling `commit_weight`, and keeps the blinding to prove their
+// ballot with `create_committed_ballot_proof`.
+#[cfg(not(target_os = "solana"))]
+pub fn commit_weight(
+    weight: u64,
+    max_weight: u64,
+    public_key: &ElGamalPubkey,
+    context: &BallotContext,
+) -> (ElGamalCiphertext, Scalar, WeightProof, BatchedRangeProofU128Data) {
+    let blinding = Scalar::random(&mut OsRng);
+    let opening = Scalar::random(&mut OsRng);
+    let ciphertext = encrypt_value(weight, &blinding, public_key);
+    let decompressed_public_point = decompress(&public_key.compressed_point).unwrap();
+    let pedersen_h = decompress(&PEDERSEN_H_BYTES).unwrap();
+    let commitment = (GENERATOR_G * Scalar::from(weight) + pedersen_h * opening).compress().to_bytes();
+
+    let weight_nonce = Scalar::random(&mut OsRng);
+    let blinding_nonce = Scalar::random(&mut OsRng);
+    let opening_nonce = Scalar::random(&mut OsRng);
+    let nonce_commitments = [
+        GENERATOR_G * blinding_nonce,
+        generator_h() * weight_nonce + decompressed_public_point * blinding_nonce,
+        GENERATOR_G * weight_nonce + pedersen_h * opening_nonce,
+    ];
+    let nonce_commitments = nonce_commitments.map(|point| point.compress().to_bytes());
+    let challenge = weight_challenge(context, public_key, max_weight, &ciphertext, &commitment, &nonce_commitments);
+
+    // A weight above `max_weight` wraps around, and the range proof is for other commitments
+    let range_proof = prove_range([weight, max_weight.wrapping_sub(weight)], [opening, -opening]);
+    let proof = WeightProof {
+        commitment,
+        challenge: challenge.to_bytes(),
+        response_weight: (weight_nonce + challenge * Scalar::from(weight)).to_bytes(),
+        response_blinding: (blinding_nonce + challenge * blinding).to_bytes(),
+        response_opening: (opening_nonce + challenge * opening).to_bytes(),
+    };
+    (ciphertext, blinding, proof, range_proof)
+}
+
+// `range_proof` is the data of the proof program's context state account for the proof. Malformed
+// points or scalars from the client fail validation instead of panicking.
+pub fn validate_weight_proof(
+    proof: &WeightProof,
+    ciphertext: &ElGamalCiphertext,
+    max_weight: u64,
+    public_key: &ElGamalPubkey,
+    context: &BallotContext,
+    range_proof: &[u8],
+) -> bool {
+    let (Some(challenge), Some(response_weight), Some(response_blinding), Some(response_opening)) = (
+        Option::<Scalar>::from(Scalar::from_canonical_bytes(proof.challenge)),
+        Option::<Scalar>::from(Scalar::from_canonical_bytes(proof.response_weight)),
+        Option::<Scalar>::from(Scalar::from_canonical_bytes(proof.response_blinding)),
+        Option::<Scalar>::from(Scalar::from_canonical_bytes(proof.response_opening)),
+    ) else {
+        return false;
+    };
+
+    // Recovers the commitments from the responses; they only hash to `challenge` if the proof holds
+    let (Some(commitment1), Some(commitment2), Some(commitment3), Some(remaining)) = (
+        multiscalar_multiply(&[response_blinding, -challenge], &[GENERATOR_G_BYTES, ciphertext.component1]),
+        multiscalar_multiply(
+            &[response_weight, response_blinding, -challenge],
+            &[GENERATOR_H_BYTES, public_key.compressed_point, ciphertext.component2],
+        ),
+        multiscalar_multiply(&[response_weight, response_opening, -challenge], &[GENERATOR_G_BYTES, PEDERSEN_H_BYTES, proof.commitment]),
+        multiscalar_multiply(&[Scalar::from(max_weight), -Scalar::ONE], &[GENERATOR_G_BYTES, proof.commitment]),
+    ) else {
+        return false;
+    };
+    let nonce_commitments = [commitment1, commitment2, commitment3];
+    weight_challenge(context, public_key, max_weight, ciphertext, &proof.commitment, &nonce_commitments) == challenge
+        && proven_in_range(range_proof, &[proof.commitment, remaining])
+}
+
+fn weight_challenge(
+    context: &BallotContext,
+    public_key: &ElGamalPubkey,
+    max_weight: u64,
+    ciphertext: &ElGamalCiphertext,
+    commitment: &[u8; 32],
+    nonce_commitments: &[[u8; 32]; 3],
+) -> Scalar {
+    let mut transcript = weight_transcript(context, public_key, max_weight, ciphertext);
+    transcript.append_message(b"pedersen_commitment", commitment);
+    transcript.append_message(b"commitment1", &nonce_commitments[0]);
+    transcript.append_message(b"commitment2", &nonce_commitments[1]);
+    transcript.append_message(b"commitment3", &nonce_commitments[2]);
+    challenge_scalar(&mut transcript)
+}
@@ -0,0 +1,209 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use curve25519_dalek::scalar::Scalar;
+use dao_zk_proof_contract_using_curve_dalek::errors::CustomError;
+use dao_zk_proof_contract_using_curve_dalek::events::*;
+use dao_zk_proof_contract_using_curve_dalek::state::*;
+use dao_zk_proof_contract_using_curve_dalek::zk_proof::*;
+use solana_sdk::pubkey::Pubkey;
+
+#[tokio::test]
+async fn records_client_encrypted_ballot() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let (ciphertext, proof) = dao.ballot_for(dao.payer(), true, dao.balance).await;
+    let ix = dao.vote_with_ix(ciphertext.clone(), proof);
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    let votes = decode_events::<VoteCast>(&logs);
+    assert_eq!(votes.len(), 1);
+    assert_eq!(votes[0].ciphertext, ciphertext);
+
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert_eq!(election.number_of_votes, 1);
+    assert_eq!(election.current, 0);
+}
+
+#[tokio::test]
+async fn rejects_token_accounts_of_other_owners_or_mints() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    dao.process(&[ix], &[]).await.unwrap();
+
+    // A whale's account, with a ballot proven for the whale's balance
+    let (_, whale_account) = dao.add_voter(10_000);
+    let (ciphertext, proof) = dao.ballot_for(dao.payer(), true, 10_000).await;
+    let ix = dao.vote_as_ix(dao.payer(), whale_account, ciphertext, proof);
+    assert_anchor_error(dao.process(&[ix], &[]).await, ErrorCode::ConstraintTokenOwner);
+
+    let other_mint_account = Pubkey::new_unique();
+    let account = token_account_data(Pubkey::new_unique(), dao.payer(), 10_000);
+    dao.context.set_account(&other_mint_account, &account.into());
+    let (ciphertext, proof) = dao.ballot_for(dao.payer(), true, 10_000).await;
+    let ix = dao.vote_as_ix(dao.payer(), other_mint_account, ciphertext, proof);
+    assert_anchor_error(dao.process(&[ix], &[]).await, ErrorCode::ConstraintTokenMint);
+}
+
+#[tokio::test]
+async fn rejects_malformed_proofs() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    dao.process(&[ix], &[]).await.unwrap();
+
+    // Not a valid Ristretto encoding
+    let (ciphertext, mut proof) = dao.ballot_for(dao.payer(), true, dao.balance).await;
+    proof.commitment1_2 = [0xff; 32];
+    let ix = dao.vote_with_ix(ciphertext, proof);
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidProof);
+
+    // Not a canonical scalar
+    let (ciphertext, mut proof) = dao.ballot_for(dao.payer(), false, dao.balance).await;
+    proof.response0 = [0xff; 32];
+    let ix = dao.vote_with_ix(ciphertext, proof);
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidProof);
+}
+
+#[tokio::test]
+async fn rejects_invalid_election_keys() {
+    let mut dao = start(100).await;
+    // The identity point, then bytes that aren't a Ristretto encoding
+    for compressed_point in [[0; 32], [0xff; 32]] {
+        let ix = dao.new_polling_with_key_ix("newSymbol", "NEW", ElGamalPubkey { compressed_point });
+        assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidEncryptionKey);
+    }
+}
+
+#[tokio::test]
+async fn verifies_ballots_against_election_key() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let (other_key, _) = create_keypair();
+    let context = dao.ballot_context(dao.payer()).await;
+    let (ciphertext, proof) = ballot(true, dao.balance, &other_key, &context);
+    let ix = dao.vote_with_ix(ciphertext, proof);
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidProof);
+
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert_eq!(election.encryption_key, dao.election_key);
+}
+
+#[test]
+fn vote_proofs_only_accept_bits() {
+    let (public_key, _) = create_keypair();
+    let context = offline_context();
+    for vote in [false, true] {
+        let (ciphertext, proof) = ballot(vote, 1, &public_key, &context);
+        assert!(validate_vote_proof(&proof, &ciphertext, 1, &public_key, &context));
+    }
+
+    // Running the honest prover on an encryption of 2, claiming either branch
+    let random_scalar = Scalar::random(&mut rand::rngs::OsRng);
+    let two = encrypt_value(2, &random_scalar, &public_key);
+    for vote in [false, true] {
+        let proof = create_ballot_proof(&two, vote, 1, random_scalar, &public_key, &context);
+        assert!(!validate_vote_proof(&proof, &two, 1, &public_key, &context));
+    }
+
+    // Reusing a valid yes proof made with the same randomness
+    let proof = create_vote_proof(true, 1, random_scalar, &public_key, &context);
+    assert!(!validate_vote_proof(&proof, &two, 1, &public_key, &context));
+
+    // Two yes ballots added together
+    let other_scalar = Scalar::random(&mut rand::rngs::OsRng);
+    let yes = encrypt_with_randomness(true, &random_scalar, &public_key);
+    let other_yes = encrypt_with_randomness(true, &other_scalar, &public_key);
+    let sum = yes.checked_add(&other_yes).unwrap();
+    let proof = create_ballot_proof(&sum, true, 1, random_scalar + other_scalar, &public_key, &context);
+    assert!(!validate_vote_proof(&proof, &sum, 1, &public_key, &context));
+
+    // A yes ballot scaled to a thousand votes
+    let scaled = yes.checked_mul(1000).unwrap();
+    let proof = create_ballot_proof(&scaled, true, 1, random_scalar * Scalar::from(1000u64), &public_key, &context);
+    assert!(!validate_vote_proof(&proof, &scaled, 1, &public_key, &context));
+}
+
+#[test]
+fn vote_proofs_bind_the_challenge_split() {
+    let (public_key, _) = create_keypair();
+    let context = offline_context();
+    let (ciphertext, proof) = ballot(true, 1, &public_key, &context);
+
+    let mut tampered = proof.clone();
+    let challenge0 = Scalar::from_canonical_bytes(proof.challenge0).unwrap();
+    tampered.challenge0 = (challenge0 + Scalar::ONE).to_bytes();
+    assert!(!validate_vote_proof(&tampered, &ciphertext, 1, &public_key, &context));
+
+    // Swapping the branches turns the yes proof into a claim about the other plaintext
+    let swapped = VoteProof {
+        commitment0_1: proof.commitment1_1,
+        commitment0_2: proof.commitment1_2,
+        commitment1_1: proof.commitment0_1,
+        commitment1_2: proof.commitment0_2,
+        challenge0: proof.challenge0,
+        response0: proof.response1,
+        response1: proof.response0,
+    };
+    assert!(!validate_vote_proof(&swapped, &ciphertext, 1, &public_key, &context));
+}
+
+#[tokio::test]
+async fn rejects_ballot_for_twice_the_weight() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let random_scalar = Scalar::random(&mut rand::rngs::OsRng);
+    let ciphertext = encrypt_value(2 * dao.balance, &random_scalar, &dao.election_key);
+    let context = dao.ballot_context(dao.payer()).await;
+    let proof = create_ballot_proof(&ciphertext, true, dao.balance, random_scalar, &dao.election_key, &context);
+    let ix = dao.vote_with_ix(ciphertext, proof);
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidProof);
+}
+
+#[test]
+fn weighted_proofs_accept_only_zero_or_the_weight() {
+    let (public_key, _) = create_keypair();
+    let context = offline_context();
+    for vote in [false, true] {
+        let (ciphertext, proof) = ballot(vote, 100, &public_key, &context);
+        assert!(validate_vote_proof(&proof, &ciphertext, 100, &public_key, &context));
+        // Checked against any other weight
+        assert!(!validate_vote_proof(&proof, &ciphertext, 99, &public_key, &context));
+        assert!(!validate_vote_proof(&proof, &ciphertext, 1000, &public_key, &context));
+    }
+
+    // Plaintexts other than 0 and 100, with either branch claimed
+    let random_scalar = Scalar::random(&mut rand::rngs::OsRng);
+    for value in [1, 99, 101, 200] {
+        let ciphertext = encrypt_value(value, &random_scalar, &public_key);
+        for vote in [false, true] {
+            let proof = create_ballot_proof(&ciphertext, vote, 100, random_scalar, &public_key, &context);
+            assert!(!validate_vote_proof(&proof, &ciphertext, 100, &public_key, &context));
+        }
+    }
+}
+
+#[tokio::test]
+async fn checks_ballot_weight_against_token_balance() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    dao.process(&[ix], &[]).await.unwrap();
+
+    // Claiming more or less weight than the voter holds
+    for weight in [1000, 1] {
+        let (ciphertext, proof) = dao.ballot_for(dao.payer(), true, weight).await;
+        let ix = dao.vote_with_ix(ciphertext, proof);
+        assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidProof);
+    }
+
+    let ix = dao.vote_ix(true).await;
+    dao.process(&[ix], &[]).await.unwrap();
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert_eq!(decrypt_tally(&election.encrypted_yes, &dao.election_secret, 100), Some(100));
+    assert_eq!(decrypt_tally(&election.encrypted_no, &dao.election_secret, 100), Some(0));
+}
@@ -0,0 +1,637 @@
+#![allow(dead_code)]
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, Event, InstructionData, ToAccountMetas};
+use base64::Engine;
+use curve25519_dalek::scalar::Scalar;
+use dao_zk_proof_contract_using_curve_dalek as program;
+use dao_zk_proof_contract_using_curve_dalek::constants::*;
+use dao_zk_proof_contract_using_curve_dalek::errors::CustomError;
+use dao_zk_proof_contract_using_curve_dalek::state::Election;
+use dao_zk_proof_contract_using_curve_dalek::zk_proof::*;
+use dao_zk_proof_contract_using_curve_dalek::VerifyingKey;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_pack::Pack;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::system_program;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_zk_token_sdk::instruction::{BatchedRangeProofU128Data, ProofType, ZkProofData};
+use solana_zk_token_sdk::zk_token_proof_instruction::{verify_batched_verify_range_proof_u128, ContextStateInfo};
+use solana_zk_token_sdk::zk_token_proof_program;
+
+// Anchor ties the account slice to the `'info` lifetime, which `processor!` can't express
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    program::entry(program_id, accounts, data)
+}
+
+// Natively, `sol_log_data` only prints to stdout, so `emit!` never reaches the transaction logs.
+// Wraps the program-test stubs and routes it through `sol_log` as a "Program data: " line instead.
+struct LogDataStubs(Box<dyn SyscallStubs>);
+
+impl SyscallStubs for LogDataStubs {
+    fn sol_log(&self, message: &str) {
+        self.0.sol_log(message)
+    }
+    fn sol_log_compute_units(&self) {
+        self.0.sol_log_compute_units()
+    }
+    fn sol_remaining_compute_units(&self) -> u64 {
+        self.0.sol_remaining_compute_units()
+    }
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        self.0.sol_invoke_signed(instruction, account_infos, signers_seeds)
+    }
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_clock_sysvar(var_addr)
+    }
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_schedule_sysvar(var_addr)
+    }
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_fees_sysvar(var_addr)
+    }
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_rent_sysvar(var_addr)
+    }
+    fn sol_get_epoch_rewards_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_rewards_sysvar(var_addr)
+    }
+    fn sol_get_last_restart_slot(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_last_restart_slot(var_addr)
+    }
+    unsafe fn sol_memcpy(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.0.sol_memcpy(dst, src, n)
+    }
+    unsafe fn sol_memmove(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.0.sol_memmove(dst, src, n)
+    }
+    unsafe fn sol_memcmp(&self, s1: *const u8, s2: *const u8, n: usize, result: *mut i32) {
+        self.0.sol_memcmp(s1, s2, n, result)
+    }
+    unsafe fn sol_memset(&self, s: *mut u8, c: u8, n: usize) {
+        self.0.sol_memset(s, c, n)
+    }
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        self.0.sol_get_return_data()
+    }
+    fn sol_set_return_data(&self, data: &[u8]) {
+        self.0.sol_set_return_data(data)
+    }
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        let fields: Vec<String> = fields.iter().map(|field| base64::engine::general_purpose::STANDARD.encode(field)).collect();
+        self.0.sol_log(&format!("Program data: {}", fields.join(" ")))
+    }
+    fn sol_get_processed_sibling_instruction(&self, index: usize) -> Option<Instruction> {
+        self.0.sol_get_processed_sibling_instruction(index)
+    }
+    fn sol_get_stack_height(&self) -> u64 {
+        self.0.sol_get_stack_height()
+    }
+}
+
+// Only the first caller sees the program-test stubs; every test shares the wrapper afterwards
+fn install_log_data_stubs() {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    INSTALL.call_once(|| {
+        let stubs = set_syscall_stubs(Box::new(NoStubs));
+        set_syscall_stubs(Box::new(LogDataStubs(stubs)));
+    });
+}
+
+struct NoStubs;
+impl SyscallStubs for NoStubs {}
+
+pub const VOTING_PERIOD: i64 = 3600;
+
+pub struct TestDao {
+    pub context: ProgramTestContext,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub verifying_key: Pubkey,
+    pub election_key: ElGamalPubkey,
+    pub election_secret: Scalar,
+    pub balance: u64, // Payer's governing-token balance, its vote weight
+}
+
+pub fn token_account_data(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::id(),
+        ..Account::default()
+    }
+}
+
+pub fn program_account<T: AccountSerialize>(account: &T) -> Account {
+    let mut data = vec![];
+    account.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: program::ID,
+        ..Account::default()
+    }
+}
+
+/// Starts the program with a governing token account and verifying key for the payer.
+pub async fn start(balance: u64) -> TestDao {
+    let program_test =
+        ProgramTest::new("dao_zk_proof_contract_using_curve_dalek", program::ID, processor!(process_instruction));
+    start_with(program_test, balance).await
+}
+
+/// The same with the program's SBF build, as `cargo test-sbf` makes it, so compute is metered.
+#[cfg(feature = "test-sbf")]
+pub async fn start_sbf(balance: u64) -> TestDao {
+    let mut program_test = ProgramTest::new("dao_zk_proof_contract_using_curve_dalek", program::ID, None);
+    program_test.prefer_bpf(true);
+    start_with(program_test, balance).await
+}
+
+async fn start_with(program_test: ProgramTest, balance: u64) -> TestDao {
+    let mut context = program_test.start_with_context().await;
+    install_log_data_stubs();
+
+    let mint = Pubkey::new_unique();
+    let token_account = Pubkey::new_unique();
+    context.set_account(&token_account, &token_account_data(mint, context.payer.pubkey(), balance).into());
+
+    let verifying_key = Pubkey::new_unique();
+    context.set_account(&verifying_key, &program_account(&VerifyingKey { key: vec![] }).into());
+
+    let (election_key, election_secret) = create_keypair();
+    TestDao {
+        context,
+        mint,
+        token_account,
+        verifying_key,
+        election_key,
+        election_secret,
+        balance,
+    }
+}
+
+pub fn election_address(creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ELECTION_SEED.as_bytes(), creator.as_ref()], &program::ID).0
+}
+
+pub fn changable_token_address(creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[CHANGABLE_TOKEN_SEED.as_bytes(), creator.as_ref()], &program::ID).0
+}
+
+pub fn user_address(voter: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[USER_SEED.as_bytes(), voter.as_ref()], &program::ID).0
+}
+
+/// Encrypts `vote × weight` the way a voter's client does, with the proof of what the ciphertext holds.
+pub fn ballot(vote: bool, weight: u64, public_key: &ElGamalPubkey, context: &BallotContext) -> (ElGamalCiphertext, VoteProof) {
+    let random_scalar = Scalar::random(&mut rand::rngs::OsRng);
+    let ciphertext = encrypt_value(if vote { weight } else { 0 }, &random_scalar, public_key);
+    (ciphertext, create_vote_proof(vote, weight, random_scalar, public_key, context))
+}
+
+/// Encrypts `vote × weight` against a committed weight ciphertext made with `weight_blinding`.
+pub fn committed_ballot(
+    vote: bool,
+    weight: u64,
+    weight_ciphertext: &ElGamalCiphertext,
+    weight_blinding: Scalar,
+    public_key: &ElGamalPubkey,
+    context: &BallotContext,
+) -> (ElGamalCiphertext, VoteProof) {
+    let random_scalar = Scalar::random(&mut rand::rngs::OsRng);
+    let ciphertext = encrypt_value(if vote { weight } else { 0 }, &random_scalar, public_key);
+    let proof = create_committed_ballot_proof(&ciphertext, vote, weight_ciphertext, weight_blinding, random_scalar, public_key, context);
+    (ciphertext, proof)
+}
+
+/// A context for proofs that never reach the program.
+pub fn offline_context() -> BallotContext {
+    BallotContext {
+        program_id: program::ID,
+        election: Pubkey::new_unique(),
+        election_id: 1,
+        voter: Pubkey::new_unique(),
+    }
+}
+
+/// What the proof program writes to a context state account for `range_proof`, if it verifies.
+pub fn range_proof_context(range_proof: &BatchedRangeProofU128Data) -> Option<Vec<u8>> {
+    range_proof.verify_proof().ok()?;
+    Some(RangeProofContextState::encode(&Pubkey::new_unique(), ProofType::BatchedRangeProofU128, range_proof.context_data()))
+}
+
+impl TestDao {
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    pub async fn process(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<(), BanksClientError> {
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            self.context.last_blockhash,
+        );
+        self.send(transaction).await
+    }
+
+    /// Sends `instructions` with `payer` paying the fee and signing alone, as a voter does from their own wallet.
+    pub async fn process_as(&mut self, payer: &Keypair, instructions: &[Instruction]) -> Result<(), BanksClientError> {
+        let transaction = self.transaction_as(payer, instructions);
+        self.send(transaction).await
+    }
+
+    // Runs the transaction on the bank directly, like `process_with_logs`. `process_transaction` queues
+    // it instead, and reports its status before releasing its account locks, so a following direct
+    // call could fail with `AccountInUse`.
+    async fn send(&mut self, transaction: Transaction) -> Result<(), BanksClientError> {
+        let result = self.context.banks_client.process_transaction_with_metadata(transaction).await?;
+        result.result.map_err(BanksClientError::TransactionError)
+    }
+
+    pub fn transaction_as(&self, payer: &Keypair, instructions: &[Instruction]) -> Transaction {
+        Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &[payer], self.context.last_blockhash)
+    }
+
+    /// Moves to a new blockhash, so a transaction that failed earlier can be sent again.
+    pub async fn refresh_blockhash(&mut self) {
+        self.context.last_blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+    }
+
+    /// Processes `instructions` and returns the program log lines.
+    pub async fn process_with_logs(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Vec<String> {
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            self.context.last_blockhash,
+        );
+        let result = self.context.banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+        result.result.unwrap();
+        result.metadata.unwrap().log_messages
+    }
+
+    /// Funds a new voter holding `balance` of the governing mint and returns it with its token account.
+    pub fn add_voter(&mut self, balance: u64) -> (Keypair, Pubkey) {
+        let voter = Keypair::new();
+        let token_account = Pubkey::new_unique();
+        self.context.set_account(&voter.pubkey(), &Account::new(1_000_000_000, 0, &system_program::ID).into());
+        self.context.set_account(&token_account, &token_account_data(self.mint, voter.pubkey(), balance).into());
+        (voter, token_account)
+    }
+
+    pub async fn account<T: AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self.context.banks_client.get_account(address).await.unwrap().unwrap();
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub fn new_polling_ix(&self, proposal_voting: &str, value: &str) -> Instruction {
+        self.new_polling_with_key_ix(proposal_voting, value, self.election_key.clone())
+    }
+
+    /// Opens a poll whose voters commit to hidden weights.
+    pub fn new_polling_hidden_ix(&self, proposal_voting: &str, value: &str) -> Instruction {
+        self.new_polling_with_ix(proposal_voting, value, self.election_key.clone(), true, 0)
+    }
+
+    /// Opens a multi-option poll with public weights.
+    pub fn new_polling_options_ix(&self, proposal_voting: &str, value: &str, options: u8) -> Instruction {
+        self.new_polling_with_ix(proposal_voting, value, self.election_key.clone(), false, options)
+    }
+
+    pub fn new_polling_with_key_ix(&self, proposal_voting: &str, value: &str, encryption_key: ElGamalPubkey) -> Instruction {
+        self.new_polling_with_ix(proposal_voting, value, encryption_key, false, 0)
+    }
+
+    fn new_polling_with_ix(
+        &self,
+        proposal_voting: &str,
+        value: &str,
+        encryption_key: ElGamalPubkey,
+        hidden_weights: bool,
+        options: u8,
+    ) -> Instruction {
+        let payer = self.payer();
+        Instruction {
+            program_id: program::ID,
+            accounts: program::accounts::NewPolling {
+                election: election_address(&payer),
+                authority: payer,
+                token_account: self.token_account,
+                changable_token_account: changable_token_address(&payer),
+                verifying_key: self.verifying_key,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: program::instruction::NewPolling {
+                token: self.mint,
+                proposal_voting: proposal_voting.to_string(),
+                value: value.to_string(),
+                additional_value: String::new(),
+                encryption_key,
+                hidden_weights,
+                options,
+                voting_period: VOTING_PERIOD,
+            }
+            .data(),
+        }
+    }
+
+    /// The context `vote` checks ballots from `voter` against, on the payer's election.
+    pub async fn ballot_context(&mut self, voter: Pubkey) -> BallotContext {
+        let election_address = election_address(&self.payer());
+        let election: Election = self.account(election_address).await;
+        BallotContext {
+            program_id: program::ID,
+            election: election_address,
+            election_id: election.id,
+            voter,
+        }
+    }
+
+    /// A ballot from `voter` with `weight` on the payer's election, encrypted to the election key.
+    pub async fn ballot_for(&mut self, voter: Pubkey, vote: bool, weight: u64) -> (ElGamalCiphertext, VoteProof) {
+        let context = self.ballot_context(voter).await;
+        ballot(vote, weight, &self.election_key, &context)
+    }
+
+    pub async fn vote_ix(&mut self, vote: bool) -> Instruction {
+        let (ciphertext, proof) = self.ballot_for(self.payer(), vote, self.balance).await;
+        self.vote_with_ix(ciphertext, proof)
+    }
+
+    pub fn vote_with_ix(&self, ciphertext: ElGamalCiphertext, proof: VoteProof) -> Instruction {
+        self.vote_as_ix(self.payer(), self.token_account, ciphertext, proof)
+    }
+
+    /// Votes on the payer's election from `voter`, weighted by `token_account`.
+    pub fn vote_as_ix(&self, voter: Pubkey, token_account: Pubkey, ciphertext: ElGamalCiphertext, proof: VoteProof) -> Instruction {
+        self.vote_ix_with(voter, token_account, None, ciphertext, proof)
+    }
+
+    /// Votes on the payer's hidden-weight election from `voter`, weighted by its weight commitment.
+    pub async fn vote_committed_ix(
+        &mut self,
+        voter: Pubkey,
+        token_account: Pubkey,
+        ciphertext: ElGamalCiphertext,
+        proof: VoteProof,
+    ) -> Instruction {
+        let weight_commitment = self.weight_commitment_address(voter).await;
+        self.vote_ix_with(voter, token_account, Some(weight_commitment), ciphertext, proof)
+    }
+
+    fn vote_ix_with(
+        &self,
+        voter: Pubkey,
+        token_account: Pubkey,
+        weight_commitment: Option<Pubkey>,
+        ciphertext: ElGamalCiphertext,
+        proof: VoteProof,
+    ) -> Instruction {
+        Instruction {
+            program_id: program::ID,
+            accounts: self.vote_accounts(voter, token_account, weight_commitment),
+            data: program::instruction::Vote { ciphertext, proof }.data(),
+        }
+    }
+
+    /// A multi-option ballot from `voter` on the payer's election, with `weight` in entry `choice`.
+    pub async fn option_ballot_for(&mut self, voter: Pubkey, choice: usize, weight: u64) -> (Vec<ElGamalCiphertext>, OptionBallotProof) {
+        let context = self.ballot_context(voter).await;
+        let election: Election = self.account(election_address(&self.payer())).await;
+        let weight_ciphertext = ElGamalCiphertext::trivial(weight);
+        let options = usize::from(election.options);
+        create_option_ballot(choice, options, weight, &weight_ciphertext, Scalar::ZERO, &self.election_key, &context)
+    }
+
+    /// Casts a multi-option ballot on the payer's public-weight election from `voter`.
+    pub fn vote_options_ix(
+        &self,
+        voter: Pubkey,
+        token_account: Pubkey,
+        ciphertexts: Vec<ElGamalCiphertext>,
+        proof: OptionBallotProof,
+    ) -> Instruction {
+        Instruction {
+            program_id: program::ID,
+            accounts: self.vote_accounts(voter, token_account, None),
+            data: program::instruction::VoteOptions { ciphertexts, proof }.data(),
+        }
+    }
+
+    fn vote_accounts(&self, voter: Pubkey, token_account: Pubkey, weight_commitment: Option<Pubkey>) -> Vec<AccountMeta> {
+        let payer = self.payer();
+        program::accounts::Vote {
+            election: election_address(&payer),
+            authority: voter,
+            token_account,
+            changable_token_account: changable_token_address(&payer),
+            user: user_address(&voter),
+            weight_commitment,
+            verifying_key: self.verifying_key,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None)
+    }
+
+    pub async fn weight_commitment_address(&mut self, voter: Pubkey) -> Pubkey {
+        let election_address = election_address(&self.payer());
+        let election: Election = self.account(election_address).await;
+        let seeds = [
+            WEIGHT_COMMITMENT_SEED.as_bytes(),
+            election_address.as_ref(),
+            &election.id.to_le_bytes(),
+            voter.as_ref(),
+        ];
+        Pubkey::find_program_address(&seeds, &program::ID).0
+    }
+
+    /// Creates a context state account for `voter` and verifies `range_proof` into it with the proof
+    /// program, in two transactions since the proof alone nearly fills one. Returns the account.
+    pub async fn verify_range_proof(&mut self, voter: &Keypair, range_proof: &BatchedRangeProofU128Data) -> Result<Pubkey, BanksClientError> {
+        let context_state = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let create = system_instruction::create_account(
+            &voter.pubkey(),
+            &context_state.pubkey(),
+            rent.minimum_balance(RANGE_PROOF_CONTEXT_LEN),
+            RANGE_PROOF_CONTEXT_LEN as u64,
+            &zk_token_proof_program::id(),
+        );
+        let transaction =
+            Transaction::new_signed_with_payer(&[create], Some(&voter.pubkey()), &[voter, &context_state], self.context.last_blockhash);
+        self.send(transaction).await?;
+        self.process_as(voter, &[self.verify_range_proof_ix(voter.pubkey(), context_state.pubkey(), range_proof)]).await?;
+        Ok(context_state.pubkey())
+    }
+
+    pub fn verify_range_proof_ix(&self, voter: Pubkey, context_state: Pubkey, range_proof: &BatchedRangeProofU128Data) -> Instruction {
+        let context_state_info = ContextStateInfo {
+            context_state_account: &context_state,
+            context_state_authority: &voter,
+        };
+        verify_batched_verify_range_proof_u128(Some(context_state_info), range_proof)
+    }
+
+    pub async fn commit_weight_ix(
+        &mut self,
+        voter: Pubkey,
+        token_account: Pubkey,
+        range_proof: Pubkey,
+        ciphertext: ElGamalCiphertext,
+        proof: WeightProof,
+    ) -> Instruction {
+        let weight_commitment = self.weight_commitment_address(voter).await;
+        Instruction {
+            program_id: program::ID,
+            accounts: program::accounts::CommitWeight {
+                election: election_address(&self.payer()),
+                weight_commitment,
+                authority: voter,
+                token_account,
+                range_proof,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: program::instruction::CommitWeight { ciphertext, proof }.data(),
+        }
+    }
+
+    pub async fn now(&mut self) -> i64 {
+        let clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp
+    }
+
+    /// Moves the clock past the payer's election deadline, when ballots close and the tally can be published.
+    pub async fn end_voting(&mut self) {
+        let election: Election = self.account(election_address(&self.payer())).await;
+        let mut clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp = election.time + 1;
+        self.context.set_sysvar(&clock);
+        self.refresh_blockhash().await;
+    }
+
+    /// Decrypts the election's sums with the election secret and proves them, as the tally authority does.
+    pub async fn publish_tally_ix(&mut self) -> Instruction {
+        let election: Election = self.account(election_address(&self.payer())).await;
+        let yes = decrypt_tally(&election.encrypted_yes, &self.election_secret, 1_000).unwrap();
+        let no = decrypt_tally(&election.encrypted_no, &self.election_secret, 1_000).unwrap();
+        let yes_proof = create_decryption_proof(&election.encrypted_yes, yes, &self.election_secret);
+        let no_proof = create_decryption_proof(&election.encrypted_no, no, &self.election_secret);
+        self.publish_tally_with_ix(yes, no, yes_proof, no_proof)
+    }
+
+    pub fn publish_tally_with_ix(&self, yes: u64, no: u64, yes_proof: DecryptionProof, no_proof: DecryptionProof) -> Instruction {
+        let payer = self.payer();
+        Instruction {
+            program_id: program::ID,
+            accounts: program::accounts::PublishTally {
+                election: election_address(&payer),
+                authority: payer,
+            }
+            .to_account_metas(None),
+            data: program::instruction::PublishTally { yes, no, yes_proof, no_proof }.data(),
+        }
+    }
+
+    /// Decrypts and proves every option's sum, as the tally authority does.
+    pub async fn publish_option_tally_ix(&mut self) -> Instruction {
+        let election: Election = self.account(election_address(&self.payer())).await;
+        let counts: Vec<u64> = election
+            .encrypted_options
+            .iter()
+            .map(|sum| decrypt_tally(sum, &self.election_secret, 1_000).unwrap())
+            .collect();
+        let proofs = election
+            .encrypted_options
+            .iter()
+            .zip(&counts)
+            .map(|(sum, count)| create_decryption_proof(sum, *count, &self.election_secret))
+            .collect();
+        self.publish_option_tally_with_ix(counts, proofs)
+    }
+
+    pub fn publish_option_tally_with_ix(&self, counts: Vec<u64>, proofs: Vec<DecryptionProof>) -> Instruction {
+        let payer = self.payer();
+        Instruction {
+            program_id: program::ID,
+            accounts: program::accounts::PublishTally {
+                election: election_address(&payer),
+                authority: payer,
+            }
+            .to_account_metas(None),
+            data: program::instruction::PublishOptionTally { counts, proofs }.data(),
+        }
+    }
+
+    pub fn to_sum_up_ix(&self) -> Instruction {
+        let payer = self.payer();
+        Instruction {
+            program_id: program::ID,
+            accounts: program::accounts::ToSumUp {
+                election: election_address(&payer),
+                authority: payer,
+                token_account: self.token_account,
+                changable_token_account: changable_token_address(&payer),
+            }
+            .to_account_metas(None),
+            data: program::instruction::ToSumUp {}.data(),
+        }
+    }
+}
+
+pub fn assert_custom_error(result: Result<(), BanksClientError>, error: CustomError) {
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, u32::from(error), "expected {}", error.name());
+        }
+        other => panic!("expected {}, got {other:?}", error.name()),
+    }
+}
+
+pub fn assert_anchor_error(result: Result<(), BanksClientError>, error: anchor_lang::error::ErrorCode) {
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, u32::from(error), "expected {}", error.name());
+        }
+        other => panic!("expected {}, got {other:?}", error.name()),
+    }
+}
+
+/// Decodes every `T` emitted through `emit!` in `logs`.
+pub fn decode_events<T: Event>(logs: &[String]) -> Vec<T> {
+    logs.iter()
+        .filter_map(|line| line.split_once("Program data: ").map(|(_, data)| data))
+        .filter_map(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+        .filter(|bytes| bytes.starts_with(&T::DISCRIMINATOR))
+        .map(|bytes| T::deserialize(&mut &bytes[8..]).unwrap())
+        .collect()
+}
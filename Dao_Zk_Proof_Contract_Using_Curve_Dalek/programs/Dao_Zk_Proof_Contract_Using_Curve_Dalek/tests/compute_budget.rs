@@ -0,0 +1,69 @@
+// The native tests never meter compute. These run the SBF build under the most a transaction can
+// request, so every proof-checking instruction is shown to land on-chain. Run with `cargo test-sbf`.
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use common::*;
+use dao_zk_proof_contract_using_curve_dalek::constants::MAX_OPTIONS;
+use dao_zk_proof_contract_using_curve_dalek::zk_proof::*;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+
+const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+
+// Sends `instruction` from `payer` with the whole transaction budget
+async fn process_metered(dao: &mut TestDao, payer: &Keypair, instruction: Instruction) {
+    let instructions = [ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNITS), instruction];
+    dao.process_as(payer, &instructions).await.unwrap();
+}
+
+#[tokio::test]
+async fn ballots_and_tallies_fit_the_compute_budget() {
+    let mut dao = start_sbf(100).await;
+    let payer = dao.context.payer.insecure_clone();
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    process_metered(&mut dao, &payer, ix).await;
+
+    let ix = dao.vote_ix(true).await;
+    process_metered(&mut dao, &payer, ix).await;
+    dao.end_voting().await;
+    let ix = dao.publish_tally_ix().await;
+    process_metered(&mut dao, &payer, ix).await;
+}
+
+#[tokio::test]
+async fn hidden_weights_fit_the_compute_budget() {
+    let mut dao = start_sbf(100).await;
+    let payer = dao.context.payer.insecure_clone();
+    let ix = dao.new_polling_hidden_ix("newSymbol", "NEW");
+    process_metered(&mut dao, &payer, ix).await;
+    let (voter, token_account) = dao.add_voter(u64::MAX);
+
+    let context = dao.ballot_context(voter.pubkey()).await;
+    let (weight, blinding, proof, range_proof) = commit_weight(u64::MAX / 3, u64::MAX, &dao.election_key, &context);
+    let range_proof = dao.verify_range_proof(&voter, &range_proof).await.unwrap();
+    let ix = dao.commit_weight_ix(voter.pubkey(), token_account, range_proof, weight.clone(), proof).await;
+    process_metered(&mut dao, &voter, ix).await;
+
+    let (ciphertext, proof) = committed_ballot(true, u64::MAX / 3, &weight, blinding, &dao.election_key, &context);
+    let ix = dao.vote_committed_ix(voter.pubkey(), token_account, ciphertext, proof).await;
+    process_metered(&mut dao, &voter, ix).await;
+}
+
+#[tokio::test]
+async fn option_ballots_fit_the_compute_budget() {
+    let mut dao = start_sbf(100).await;
+    let payer = dao.context.payer.insecure_clone();
+    let ix = dao.new_polling_options_ix("poll", "", MAX_OPTIONS);
+    process_metered(&mut dao, &payer, ix).await;
+    let (voter, token_account) = dao.add_voter(40);
+
+    let (ciphertexts, proof) = dao.option_ballot_for(voter.pubkey(), 1, 40).await;
+    let ix = dao.vote_options_ix(voter.pubkey(), token_account, ciphertexts, proof);
+    process_metered(&mut dao, &voter, ix).await;
+    dao.end_voting().await;
+    let ix = dao.publish_option_tally_ix().await;
+    process_metered(&mut dao, &payer, ix).await;
+}
@@ -0,0 +1,78 @@
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use dao_zk_proof_contract_using_curve_dalek::zk_proof::*;
+use rand::rngs::OsRng;
+use sha3::Sha3_512;
+
+fn point(bytes: &[u8; 32]) -> RistrettoPoint {
+    CompressedRistretto::from_slice(bytes).unwrap().decompress().unwrap()
+}
+
+fn key(point: RistrettoPoint) -> ElGamalPubkey {
+    ElGamalPubkey { compressed_point: point.compress().to_bytes() }
+}
+
+#[test]
+fn generators_are_distinct_and_not_identity() {
+    let h = generator_h();
+    assert_ne!(GENERATOR_G, RistrettoPoint::identity());
+    assert_ne!(h, RistrettoPoint::identity());
+    assert_ne!(h, GENERATOR_G);
+    assert_eq!(h, generator_h());
+}
+
+// The program uses these compressed instead of hashing to the curve on every call
+#[test]
+fn precomputed_generators_match_their_derivation() {
+    assert_eq!(generator_h(), RistrettoPoint::hash_from_bytes::<Sha3_512>(GENERATOR_H_LABEL));
+    assert_eq!(GENERATOR_G_BYTES, GENERATOR_G.compress().to_bytes());
+    assert_eq!(PEDERSEN_H_BYTES, solana_zk_token_sdk::encryption::pedersen::H.compress().to_bytes());
+}
+
+#[test]
+fn decrypts_only_with_the_secret() {
+    let (public_key, secret) = create_keypair();
+    let (_, other_secret) = create_keypair();
+    for vote in [false, true] {
+        let ciphertext = perform_encryption(vote, &public_key);
+        assert_eq!(perform_decryption(&ciphertext, &secret), vote);
+    }
+    assert!(!perform_decryption(&perform_encryption(true, &public_key), &other_secret));
+}
+
+// The checks that read every vote under the identity "generator" tell nothing apart now
+#[test]
+fn ciphertexts_of_zero_and_one_share_no_visible_structure() {
+    let (public_key, _) = create_keypair();
+    let h = generator_h();
+    let zeros: Vec<_> = (0..8).map(|_| perform_encryption(false, &public_key)).collect();
+    let ones: Vec<_> = (0..8).map(|_| perform_encryption(true, &public_key)).collect();
+
+    for ciphertext in zeros.iter().chain(&ones) {
+        let (component1, component2) = (point(&ciphertext.component1), point(&ciphertext.component2));
+        assert_ne!(component1, RistrettoPoint::identity());
+        assert_ne!(component2, RistrettoPoint::identity());
+        assert_ne!(component2, h);
+        assert_ne!(component2 - h, RistrettoPoint::identity());
+    }
+    // Encryption is randomized, so equal votes don't give equal ciphertexts
+    for ciphertexts in [&zeros, &ones] {
+        for (i, ciphertext) in ciphertexts.iter().enumerate() {
+            assert!(ciphertexts[i + 1..].iter().all(|other| other.component2 != ciphertext.component2));
+        }
+    }
+}
+
+// Telling the votes apart takes the relation between the key and r·G, a DDH instance: the same
+// ciphertext is a yes vote under one key and a no vote under another
+#[test]
+fn ciphertext_alone_fits_either_vote() {
+    let (public_key, _) = create_keypair();
+    let random_scalar = Scalar::random(&mut OsRng);
+    let yes = encrypt_with_randomness(true, &random_scalar, &public_key);
+
+    let other_key = key(point(&public_key.compressed_point) + generator_h() * random_scalar.invert());
+    let no = encrypt_with_randomness(false, &random_scalar, &other_key);
+    assert_eq!(yes, no);
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::*;
+use dao_zk_proof_contract_using_curve_dalek::events::*;
+use dao_zk_proof_contract_using_curve_dalek::state::Election;
+
+#[tokio::test]
+async fn emits_proposal_lifecycle_events() {
+    let mut dao = start(100).await;
+    let election = election_address(&dao.payer());
+
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    let created = decode_events::<ProposalCreated>(&logs);
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].election, election);
+    assert_eq!(created[0].creator, dao.payer());
+    assert_eq!(created[0].token, dao.mint);
+    assert_eq!(created[0].proposal_voting, "newSymbol");
+    assert_eq!(created[0].value, "NEW");
+    let election_account: Election = dao.account(election).await;
+    assert_eq!(created[0].voting_ends_at, election_account.time);
+
+    let ix = dao.vote_ix(true).await;
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    let votes = decode_events::<VoteCast>(&logs);
+    assert_eq!(votes.len(), 1);
+    assert_eq!(votes[0].election, election);
+    assert_eq!(votes[0].voter, dao.payer());
+    assert_eq!(votes[0].weight, 100);
+    let rewards = decode_events::<RewardGranted>(&logs);
+    assert_eq!(rewards.len(), 1);
+    assert_eq!(rewards[0].user, dao.payer());
+    assert_eq!(rewards[0].reward_points, 1);
+
+    dao.end_voting().await;
+    let ix = dao.publish_tally_ix().await;
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    let published = decode_events::<TallyPublished>(&logs);
+    assert_eq!(published.len(), 1);
+    assert_eq!(published[0].election, election);
+    assert_eq!(published[0].yes, 100);
+    assert_eq!(published[0].no, 0);
+
+    let ix = dao.to_sum_up_ix();
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    let finalized = decode_events::<ProposalFinalized>(&logs);
+    assert_eq!(finalized.len(), 1);
+    assert!(finalized[0].passed);
+    assert_eq!(finalized[0].current, 100);
+    assert_eq!(finalized[0].number_of_votes, 1);
+    let executed = decode_events::<ProposalExecuted>(&logs);
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].executor, dao.payer());
+    assert_eq!(executed[0].proposal_voting, "newSymbol");
+    assert_eq!(executed[0].value, "NEW");
+}
+
+#[tokio::test]
+async fn rejected_proposal_is_not_executed() {
+    let mut dao = start(100).await;
+
+    let ix = dao.new_polling_ix("newName", "New Name");
+    dao.process(&[ix], &[]).await.unwrap();
+    let ix = dao.vote_ix(false).await;
+    dao.process(&[ix], &[]).await.unwrap();
+    dao.end_voting().await;
+    let ix = dao.publish_tally_ix().await;
+    dao.process(&[ix], &[]).await.unwrap();
+    let ix = dao.to_sum_up_ix();
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    let finalized = decode_events::<ProposalFinalized>(&logs);
+    assert_eq!(finalized.len(), 1);
+    assert!(!finalized[0].passed);
+    assert_eq!(finalized[0].current, -100);
+    assert!(decode_events::<ProposalExecuted>(&logs).is_empty());
+}
@@ -0,0 +1,238 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use curve25519_dalek::scalar::Scalar;
+use dao_zk_proof_contract_using_curve_dalek::errors::CustomError;
+use dao_zk_proof_contract_using_curve_dalek::events::*;
+use dao_zk_proof_contract_using_curve_dalek::state::*;
+use dao_zk_proof_contract_using_curve_dalek::zk_proof::*;
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_program;
+use solana_zk_token_sdk::instruction::ProofType;
+
+#[test]
+fn weight_proofs_accept_weights_up_to_balance() {
+    let (public_key, _) = create_keypair();
+    let context = offline_context();
+    for weight in [0, 1, 37, 100] {
+        let (ciphertext, _, proof, range_proof) = commit_weight(weight, 100, &public_key, &context);
+        let range_proof = range_proof_context(&range_proof).unwrap();
+        assert!(validate_weight_proof(&proof, &ciphertext, 100, &public_key, &context, &range_proof));
+    }
+    // The range proof verifies, but for the wrapped remainder rather than 100 - weight
+    for weight in [101, u64::MAX] {
+        let (ciphertext, _, proof, range_proof) = commit_weight(weight, 100, &public_key, &context);
+        let range_proof = range_proof_context(&range_proof).unwrap();
+        assert!(!validate_weight_proof(&proof, &ciphertext, 100, &public_key, &context, &range_proof));
+    }
+}
+
+#[test]
+fn weight_proofs_are_bound_to_their_statement() {
+    let (public_key, _) = create_keypair();
+    let context = offline_context();
+    let (ciphertext, _, proof, range_proof) = commit_weight(37, 100, &public_key, &context);
+    let range_proof = range_proof_context(&range_proof).unwrap();
+
+    // Another balance, voter or key
+    assert!(!validate_weight_proof(&proof, &ciphertext, 50, &public_key, &context, &range_proof));
+    let other_voter = BallotContext { voter: Pubkey::new_unique(), ..context.clone() };
+    assert!(!validate_weight_proof(&proof, &ciphertext, 100, &public_key, &other_voter, &range_proof));
+    let (other_key, _) = create_keypair();
+    assert!(!validate_weight_proof(&proof, &ciphertext, 100, &other_key, &context, &range_proof));
+
+    // A first component that doesn't match the blinding in the commitment
+    let other = encrypt_value(0, &Scalar::ONE, &public_key);
+    let mismatched = ElGamalCiphertext { component1: other.component1, ..ciphertext.clone() };
+    assert!(!validate_weight_proof(&proof, &mismatched, 100, &public_key, &context, &range_proof));
+
+    // A range proof of another commitment, or context state of another proof type
+    let (_, _, _, other_range_proof) = commit_weight(37, 100, &public_key, &context);
+    let other_range_proof = range_proof_context(&other_range_proof).unwrap();
+    assert!(!validate_weight_proof(&proof, &ciphertext, 100, &public_key, &context, &other_range_proof));
+    let mut other_type = range_proof.clone();
+    other_type[32] = ProofType::BatchedRangeProofU64 as u8;
+    assert!(!validate_weight_proof(&proof, &ciphertext, 100, &public_key, &context, &other_type));
+}
+
+#[test]
+fn committed_ballots_encrypt_zero_or_the_committed_weight() {
+    let (public_key, secret) = create_keypair();
+    let context = offline_context();
+    let (weight, blinding, _, _) = commit_weight(37, 100, &public_key, &context);
+
+    for vote in [false, true] {
+        let (ciphertext, proof) = committed_ballot(vote, 37, &weight, blinding, &public_key, &context);
+        assert!(validate_committed_vote_proof(&proof, &ciphertext, &weight, &public_key, &context));
+        assert_eq!(decrypt_tally(&ciphertext, &secret, 100), Some(if vote { 37 } else { 0 }));
+    }
+
+    // Voting the whole balance, or another weight, against the commitment
+    for claimed in [100, 38] {
+        let (ciphertext, proof) = committed_ballot(true, claimed, &weight, blinding, &public_key, &context);
+        assert!(!validate_committed_vote_proof(&proof, &ciphertext, &weight, &public_key, &context));
+    }
+}
+
+struct Voter {
+    keypair: Keypair,
+    token_account: Pubkey,
+}
+
+impl Voter {
+    fn new(dao: &mut TestDao, balance: u64) -> Self {
+        let (keypair, token_account) = dao.add_voter(balance);
+        Voter { keypair, token_account }
+    }
+}
+
+// Opens a hidden-weight poll with a voter holding 40
+async fn setup() -> (TestDao, Voter) {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_hidden_ix("newSymbol", "NEW");
+    dao.process(&[ix], &[]).await.unwrap();
+    let voter = Voter::new(&mut dao, 40);
+    (dao, voter)
+}
+
+// Commits `weight` for `voter` and returns its ciphertext and blinding
+async fn commit(dao: &mut TestDao, voter: &Voter, weight: u64, balance: u64) -> (ElGamalCiphertext, Scalar) {
+    let context = dao.ballot_context(voter.keypair.pubkey()).await;
+    let (ciphertext, blinding, proof, range_proof) = commit_weight(weight, balance, &dao.election_key, &context);
+    let range_proof = dao.verify_range_proof(&voter.keypair, &range_proof).await.unwrap();
+    let ix = dao.commit_weight_ix(voter.keypair.pubkey(), voter.token_account, range_proof, ciphertext.clone(), proof).await;
+    dao.process_as(&voter.keypair, &[ix]).await.unwrap();
+    (ciphertext, blinding)
+}
+
+// Size of the transaction a voter sends from their own wallet
+fn transaction_size(dao: &TestDao, voter: &Voter, instruction: Instruction) -> usize {
+    bincode::serialize(&dao.transaction_as(&voter.keypair, &[instruction])).unwrap().len()
+}
+
+#[tokio::test]
+async fn tallies_hidden_weights() {
+    let (mut dao, voter) = setup().await;
+    let whale = Voter::new(&mut dao, 100);
+
+    let (whale_weight, whale_blinding) = commit(&mut dao, &whale, 60, 100).await;
+    let (voter_weight, voter_blinding) = commit(&mut dao, &voter, 25, 40).await;
+    let address = dao.weight_commitment_address(whale.keypair.pubkey()).await;
+    let commitment: WeightCommitment = dao.account(address).await;
+    assert_eq!(commitment.ciphertext, whale_weight);
+    assert_eq!(commitment.max_weight, 100);
+
+    let context = dao.ballot_context(whale.keypair.pubkey()).await;
+    let (ciphertext, proof) = committed_ballot(true, 60, &whale_weight, whale_blinding, &dao.election_key, &context);
+    let ix = dao.vote_committed_ix(whale.keypair.pubkey(), whale.token_account, ciphertext, proof).await;
+    let logs = dao.process_with_logs(&[ix], &[&whale.keypair]).await;
+    // Neither the choice nor the weight is visible
+    assert_eq!(decode_events::<VoteCast>(&logs)[0].weight, 0);
+
+    let context = dao.ballot_context(voter.keypair.pubkey()).await;
+    let (ciphertext, proof) = committed_ballot(false, 25, &voter_weight, voter_blinding, &dao.election_key, &context);
+    let ix = dao.vote_committed_ix(voter.keypair.pubkey(), voter.token_account, ciphertext, proof).await;
+    dao.process(&[ix], &[&voter.keypair]).await.unwrap();
+
+    dao.end_voting().await;
+    let ix = dao.publish_tally_ix().await;
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    let published = decode_events::<TallyPublished>(&logs);
+    assert_eq!(published[0].yes, 60);
+    assert_eq!(published[0].no, 25);
+}
+
+#[tokio::test]
+async fn rejects_votes_without_a_valid_weight_commitment() {
+    let (mut dao, voter) = setup().await;
+    let voter_pubkey = voter.keypair.pubkey();
+
+    // Voting the public balance, with no commitment
+    let (ciphertext, proof) = dao.ballot_for(voter_pubkey, true, 40).await;
+    let ix = dao.vote_as_ix(voter_pubkey, voter.token_account, ciphertext, proof);
+    assert_custom_error(dao.process(&[ix], &[&voter.keypair]).await, CustomError::MissingWeightCommitment);
+
+    // Committing more than the balance
+    let context = dao.ballot_context(voter_pubkey).await;
+    let (ciphertext, _, proof, range_proof) = commit_weight(41, 40, &dao.election_key, &context);
+    let range_proof = dao.verify_range_proof(&voter.keypair, &range_proof).await.unwrap();
+    let ix = dao.commit_weight_ix(voter_pubkey, voter.token_account, range_proof, ciphertext, proof).await;
+    assert_custom_error(dao.process_as(&voter.keypair, &[ix]).await, CustomError::InvalidProof);
+
+    // A ballot for the whole balance against a smaller commitment
+    let (weight, blinding) = commit(&mut dao, &voter, 10, 40).await;
+    let (ciphertext, proof) = committed_ballot(true, 40, &weight, blinding, &dao.election_key, &context);
+    let ix = dao.vote_committed_ix(voter_pubkey, voter.token_account, ciphertext, proof).await;
+    assert_custom_error(dao.process(&[ix], &[&voter.keypair]).await, CustomError::InvalidProof);
+}
+
+#[tokio::test]
+async fn rejects_weights_backed_by_another_mint() {
+    let (mut dao, voter) = setup().await;
+    let voter_pubkey = voter.keypair.pubkey();
+    let other_mint_account = Pubkey::new_unique();
+    let account = token_account_data(Pubkey::new_unique(), voter_pubkey, 10_000);
+    dao.context.set_account(&other_mint_account, &account.into());
+
+    let context = dao.ballot_context(voter_pubkey).await;
+    let (ciphertext, _, proof, range_proof) = commit_weight(5_000, 10_000, &dao.election_key, &context);
+    let range_proof = dao.verify_range_proof(&voter.keypair, &range_proof).await.unwrap();
+    let ix = dao.commit_weight_ix(voter_pubkey, other_mint_account, range_proof, ciphertext, proof).await;
+    assert_anchor_error(dao.process_as(&voter.keypair, &[ix]).await, ErrorCode::ConstraintTokenMint);
+}
+
+#[tokio::test]
+async fn rejects_weight_commitments_in_public_elections() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let context = dao.ballot_context(dao.payer()).await;
+    let (ciphertext, _, proof, range_proof) = commit_weight(50, 100, &dao.election_key, &context);
+    let payer = dao.context.payer.insecure_clone();
+    let range_proof = dao.verify_range_proof(&payer, &range_proof).await.unwrap();
+    let ix = dao.commit_weight_ix(dao.payer(), dao.token_account, range_proof, ciphertext, proof).await;
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::WeightsNotHidden);
+}
+
+#[tokio::test]
+async fn hidden_weight_transactions_fit_a_packet() {
+    let (mut dao, voter) = setup().await;
+    let voter_pubkey = voter.keypair.pubkey();
+    let context = dao.ballot_context(voter_pubkey).await;
+
+    let (weight, blinding, proof, range_proof) = commit_weight(25, 40, &dao.election_key, &context);
+    let ix = dao.verify_range_proof_ix(voter_pubkey, Pubkey::new_unique(), &range_proof);
+    assert!(transaction_size(&dao, &voter, ix) <= PACKET_DATA_SIZE);
+    let ix = dao.commit_weight_ix(voter_pubkey, voter.token_account, Pubkey::new_unique(), weight.clone(), proof).await;
+    assert!(transaction_size(&dao, &voter, ix) <= PACKET_DATA_SIZE);
+
+    let (ciphertext, proof) = committed_ballot(true, 25, &weight, blinding, &dao.election_key, &context);
+    let ix = dao.vote_committed_ix(voter_pubkey, voter.token_account, ciphertext, proof).await;
+    assert!(transaction_size(&dao, &voter, ix) <= PACKET_DATA_SIZE);
+}
+
+#[tokio::test]
+async fn rejects_range_proofs_the_proof_program_did_not_write() {
+    let (mut dao, voter) = setup().await;
+    let voter_pubkey = voter.keypair.pubkey();
+    let context = dao.ballot_context(voter_pubkey).await;
+    let (ciphertext, _, proof, range_proof) = commit_weight(25, 40, &dao.election_key, &context);
+
+    // The context state the proof program would write, forged into an account it doesn't own
+    let forged = Pubkey::new_unique();
+    let account = Account {
+        lamports: 1_000_000_000,
+        data: range_proof_context(&range_proof).unwrap(),
+        owner: system_program::ID,
+        ..Account::default()
+    };
+    dao.context.set_account(&forged, &account.into());
+    let ix = dao.commit_weight_ix(voter_pubkey, voter.token_account, forged, ciphertext, proof).await;
+    assert_anchor_error(dao.process_as(&voter.keypair, &[ix]).await, ErrorCode::ConstraintOwner);
+}
@@ -0,0 +1,204 @@
+mod common;
+
+use common::*;
+use curve25519_dalek::scalar::Scalar;
+use dao_zk_proof_contract_using_curve_dalek::constants::MAX_OPTIONS;
+use dao_zk_proof_contract_using_curve_dalek::errors::CustomError;
+use dao_zk_proof_contract_using_curve_dalek::events::*;
+use dao_zk_proof_contract_using_curve_dalek::state::*;
+use dao_zk_proof_contract_using_curve_dalek::zk_proof::*;
+use rand::rngs::OsRng;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+
+// Entries encrypting `values` under fresh randomness, proven as if `votes` were the choices
+fn forged_ballot(
+    values: &[ElGamalCiphertext],
+    votes: &[bool],
+    weight: u64,
+    public_key: &ElGamalPubkey,
+    context: &BallotContext,
+) -> (Vec<ElGamalCiphertext>, OptionBallotProof) {
+    let random_scalars: Vec<Scalar> = values.iter().map(|_| Scalar::random(&mut OsRng)).collect();
+    let ciphertexts: Vec<ElGamalCiphertext> = values
+        .iter()
+        .zip(&random_scalars)
+        .map(|(value, random_scalar)| encrypt_value(0, random_scalar, public_key).checked_add(value).unwrap())
+        .collect();
+    let weight = ElGamalCiphertext::trivial(weight);
+    let proof = create_option_ballot_proof(&ciphertexts, votes, &weight, Scalar::ZERO, &random_scalars, public_key, context);
+    (ciphertexts, proof)
+}
+
+#[test]
+fn option_ballots_put_the_weight_in_exactly_one_entry() {
+    let (public_key, secret) = create_keypair();
+    let context = offline_context();
+    let weight = ElGamalCiphertext::trivial(40);
+
+    for choice in 0..3 {
+        let (ciphertexts, proof) = create_option_ballot(choice, 3, 40, &weight, Scalar::ZERO, &public_key, &context);
+        assert!(validate_option_ballot_proof(&proof, &ciphertexts, &weight, &public_key, &context));
+        let plaintexts: Vec<u64> = ciphertexts.iter().map(|entry| decrypt_tally(entry, &secret, 40).unwrap()).collect();
+        let expected: Vec<u64> = (0..3).map(|option| if option == choice { 40 } else { 0 }).collect();
+        assert_eq!(plaintexts, expected);
+    }
+}
+
+#[test]
+fn rejects_ballots_without_exactly_one_choice() {
+    let (public_key, _) = create_keypair();
+    let context = offline_context();
+    let weight = ElGamalCiphertext::trivial(40);
+    let zero = ElGamalCiphertext::default();
+
+    // Two choices, or none
+    let both = [weight.clone(), weight.clone(), zero.clone()];
+    let (ciphertexts, proof) = forged_ballot(&both, &[true, true, false], 40, &public_key, &context);
+    assert!(!validate_option_ballot_proof(&proof, &ciphertexts, &weight, &public_key, &context));
+    let none = [zero.clone(), zero.clone(), zero.clone()];
+    let (ciphertexts, proof) = forged_ballot(&none, &[false, false, false], 40, &public_key, &context);
+    assert!(!validate_option_ballot_proof(&proof, &ciphertexts, &weight, &public_key, &context));
+
+    // Entries that add up to the weight, but aren't each 0 or the weight
+    let skewed = [ElGamalCiphertext::trivial(80), zero.checked_sub(&weight).unwrap(), zero];
+    let (ciphertexts, proof) = forged_ballot(&skewed, &[true, false, false], 40, &public_key, &context);
+    assert!(!validate_option_ballot_proof(&proof, &ciphertexts, &weight, &public_key, &context));
+}
+
+#[test]
+fn option_proofs_are_bound_to_their_ballot() {
+    let (public_key, _) = create_keypair();
+    let context = offline_context();
+    let weight = ElGamalCiphertext::trivial(40);
+    let (ciphertexts, proof) = create_option_ballot(1, 3, 40, &weight, Scalar::ZERO, &public_key, &context);
+
+    // Another voter or weight
+    let other_voter = BallotContext { voter: Pubkey::new_unique(), ..context.clone() };
+    assert!(!validate_option_ballot_proof(&proof, &ciphertexts, &weight, &public_key, &other_voter));
+    let other_weight = ElGamalCiphertext::trivial(41);
+    assert!(!validate_option_ballot_proof(&proof, &ciphertexts, &other_weight, &public_key, &context));
+
+    // Entries moved to another option
+    let mut reordered = ciphertexts.clone();
+    reordered.swap(0, 1);
+    assert!(!validate_option_ballot_proof(&proof, &reordered, &weight, &public_key, &context));
+
+    // A missing entry
+    let mut truncated = proof.clone();
+    truncated.entries.pop();
+    assert!(!validate_option_ballot_proof(&truncated, &ciphertexts[..2], &weight, &public_key, &context));
+    assert!(!validate_option_ballot_proof(&truncated, &ciphertexts, &weight, &public_key, &context));
+}
+
+#[tokio::test]
+async fn tallies_one_sum_per_option() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_options_ix("newSymbol", "NEW", 3);
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let (ciphertexts, proof) = dao.option_ballot_for(dao.payer(), 2, 100).await;
+    let ix = dao.vote_options_ix(dao.payer(), dao.token_account, ciphertexts.clone(), proof);
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    let cast = decode_events::<OptionVoteCast>(&logs);
+    assert_eq!(cast[0].ciphertexts, ciphertexts);
+    assert_eq!(cast[0].weight, 100);
+
+    for (balance, choice) in [(40, 0), (7, 2)] {
+        let (voter, token_account) = dao.add_voter(balance);
+        let (ciphertexts, proof) = dao.option_ballot_for(voter.pubkey(), choice, balance).await;
+        let ix = dao.vote_options_ix(voter.pubkey(), token_account, ciphertexts, proof);
+        dao.process(&[ix], &[&voter]).await.unwrap();
+    }
+
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert_eq!(election.number_of_votes, 3);
+    let sums: Vec<u64> = election
+        .encrypted_options
+        .iter()
+        .map(|sum| decrypt_tally(sum, &dao.election_secret, 147).unwrap())
+        .collect();
+    assert_eq!(sums, [40, 0, 107]);
+
+    // Sealed until the deadline, which also closes the ballots; publishing closes them for good
+    let ix = dao.publish_option_tally_ix().await;
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::VotingTime);
+    dao.end_voting().await;
+    let (voter, token_account) = dao.add_voter(500);
+    let (ciphertexts, proof) = dao.option_ballot_for(voter.pubkey(), 1, 500).await;
+    let ix = dao.vote_options_ix(voter.pubkey(), token_account, ciphertexts.clone(), proof.clone());
+    assert_custom_error(dao.process(&[ix], &[&voter]).await, CustomError::VotingClosed);
+
+    let ix = dao.publish_option_tally_ix().await;
+    let logs = dao.process_with_logs(&[ix], &[]).await;
+    assert_eq!(decode_events::<OptionTallyPublished>(&logs)[0].counts, [40, 0, 107]);
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert!(election.tally_published);
+    assert_eq!(election.current, 0);
+
+    dao.refresh_blockhash().await;
+    let ix = dao.vote_options_ix(voter.pubkey(), token_account, ciphertexts, proof);
+    assert_custom_error(dao.process(&[ix], &[&voter]).await, CustomError::TallyAlreadyPublished);
+}
+
+#[tokio::test]
+async fn rejects_ballots_that_do_not_match_the_poll() {
+    let mut dao = start(100).await;
+    for options in [1, MAX_OPTIONS + 1] {
+        let ix = dao.new_polling_options_ix("newSymbol", "NEW", options);
+        assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidOptionCount);
+    }
+    let ix = dao.new_polling_options_ix("newSymbol", "NEW", 3);
+    dao.process(&[ix], &[]).await.unwrap();
+
+    // A yes/no ballot, or an option ballot with an entry missing
+    let ix = dao.vote_ix(true).await;
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::OptionMismatch);
+    let (mut ciphertexts, mut proof) = dao.option_ballot_for(dao.payer(), 0, 100).await;
+    ciphertexts.pop();
+    proof.entries.pop();
+    let ix = dao.vote_options_ix(dao.payer(), dao.token_account, ciphertexts, proof);
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::OptionMismatch);
+
+    // A weight other than the balance
+    let (ciphertexts, proof) = dao.option_ballot_for(dao.payer(), 0, 50).await;
+    let ix = dao.vote_options_ix(dao.payer(), dao.token_account, ciphertexts, proof);
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidProof);
+
+    dao.end_voting().await;
+    // The yes/no tally doesn't apply, and option counts must cover every option
+    let ix = dao.publish_tally_ix().await;
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::OptionMismatch);
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    let proof = create_decryption_proof(&election.encrypted_options[0], 0, &dao.election_secret);
+    let ix = dao.publish_option_tally_with_ix(vec![0], vec![proof]);
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::OptionMismatch);
+}
+
+#[tokio::test]
+async fn rejects_option_ballots_in_yes_no_polls() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let context = dao.ballot_context(dao.payer()).await;
+    let weight = ElGamalCiphertext::trivial(100);
+    let (ciphertexts, proof) = create_option_ballot(0, 2, 100, &weight, Scalar::ZERO, &dao.election_key, &context);
+    let ix = dao.vote_options_ix(dao.payer(), dao.token_account, ciphertexts, proof);
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::OptionMismatch);
+}
+
+#[tokio::test]
+async fn largest_option_ballot_fits_a_packet() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_options_ix("newSymbol", "NEW", MAX_OPTIONS);
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let (voter, token_account) = dao.add_voter(40);
+    let (ciphertexts, proof) = dao.option_ballot_for(voter.pubkey(), 3, 40).await;
+    let ix = dao.vote_options_ix(voter.pubkey(), token_account, ciphertexts, proof);
+    let transaction = dao.transaction_as(&voter, std::slice::from_ref(&ix));
+    assert!(bincode::serialize(&transaction).unwrap().len() <= PACKET_DATA_SIZE);
+    dao.process_as(&voter, &[ix]).await.unwrap();
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use common::*;
+use dao_zk_proof_contract_using_curve_dalek::errors::CustomError;
+use dao_zk_proof_contract_using_curve_dalek::state::*;
+use dao_zk_proof_contract_using_curve_dalek::zk_proof::*;
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn accumulates_weighted_ballots_under_encryption() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert_eq!(decrypt_tally(&election.encrypted_yes, &dao.election_secret, 0), Some(0));
+    assert_eq!(decrypt_tally(&election.encrypted_no, &dao.election_secret, 0), Some(0));
+
+    let ix = dao.vote_ix(true).await;
+    dao.process(&[ix], &[]).await.unwrap();
+    let (voter, token_account) = dao.add_voter(40);
+    let (ciphertext, proof) = dao.ballot_for(voter.pubkey(), false, 40).await;
+    let ix = dao.vote_as_ix(voter.pubkey(), token_account, ciphertext, proof);
+    dao.process(&[ix], &[&voter]).await.unwrap();
+    let (voter, token_account) = dao.add_voter(7);
+    let (ciphertext, proof) = dao.ballot_for(voter.pubkey(), true, 7).await;
+    let ix = dao.vote_as_ix(voter.pubkey(), token_account, ciphertext, proof);
+    dao.process(&[ix], &[&voter]).await.unwrap();
+
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert_eq!(election.number_of_votes, 3);
+    assert_eq!(election.current, 0);
+    assert_eq!(decrypt_tally(&election.encrypted_yes, &dao.election_secret, 147), Some(107));
+    assert_eq!(decrypt_tally(&election.encrypted_no, &dao.election_secret, 147), Some(40));
+
+    // Without the election secret the sums don't open
+    let (_, other_secret) = create_keypair();
+    assert_eq!(decrypt_tally(&election.encrypted_yes, &other_secret, 147), None);
+}
+
+#[tokio::test]
+async fn tally_hides_which_side_a_ballot_joined() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    dao.process(&[ix], &[]).await.unwrap();
+    let ix = dao.vote_ix(false).await;
+    dao.process(&[ix], &[]).await.unwrap();
+
+    // Both sums change on every vote, and neither equals a public encryption of the weight
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert_ne!(election.encrypted_yes, ElGamalCiphertext::default());
+    assert_ne!(election.encrypted_no, ElGamalCiphertext::default());
+    assert_ne!(election.encrypted_no, ElGamalCiphertext::trivial(100));
+    assert_eq!(decrypt_tally(&election.encrypted_yes, &dao.election_secret, 100), Some(0));
+    assert_eq!(decrypt_tally(&election.encrypted_no, &dao.election_secret, 100), Some(100));
+}
+
+#[tokio::test]
+async fn publishes_proven_tally_before_finalizing() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_ix("newName", "New Name");
+    dao.process(&[ix], &[]).await.unwrap();
+    let ix = dao.vote_ix(true).await;
+    dao.process(&[ix], &[]).await.unwrap();
+    let (voter, token_account) = dao.add_voter(30);
+    let (ciphertext, proof) = dao.ballot_for(voter.pubkey(), false, 30).await;
+    let ix = dao.vote_as_ix(voter.pubkey(), token_account, ciphertext, proof);
+    dao.process(&[ix], &[&voter]).await.unwrap();
+
+    let ix = dao.to_sum_up_ix();
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::TallyNotPublished);
+
+    dao.end_voting().await;
+    let ix = dao.publish_tally_ix().await;
+    dao.process(&[ix], &[]).await.unwrap();
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert!(election.tally_published);
+    assert_eq!(election.current, 70);
+
+    // The tally is final once published
+    let ix = dao.publish_tally_ix().await;
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::TallyAlreadyPublished);
+    let (voter, token_account) = dao.add_voter(500);
+    let (ciphertext, proof) = dao.ballot_for(voter.pubkey(), false, 500).await;
+    let ix = dao.vote_as_ix(voter.pubkey(), token_account, ciphertext, proof);
+    assert_custom_error(dao.process(&[ix], &[&voter]).await, CustomError::TallyAlreadyPublished);
+
+    dao.refresh_blockhash().await;
+    let ix = dao.to_sum_up_ix();
+    dao.process(&[ix], &[]).await.unwrap();
+    let changable: ChangableTokenAccount = dao.account(changable_token_address(&dao.payer())).await;
+    assert_eq!(changable.name, "New Name");
+}
+
+#[tokio::test]
+async fn keeps_the_tally_sealed_until_the_deadline() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    dao.process(&[ix], &[]).await.unwrap();
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    assert_eq!(election.time, dao.now().await + VOTING_PERIOD);
+    let ix = dao.vote_ix(true).await;
+    dao.process(&[ix], &[]).await.unwrap();
+
+    // The key holder could otherwise publish as soon as the running sums suit them
+    let ix = dao.publish_tally_ix().await;
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::VotingTime);
+
+    dao.end_voting().await;
+    let (voter, token_account) = dao.add_voter(500);
+    let (ciphertext, proof) = dao.ballot_for(voter.pubkey(), false, 500).await;
+    let ix = dao.vote_as_ix(voter.pubkey(), token_account, ciphertext, proof);
+    assert_custom_error(dao.process(&[ix], &[&voter]).await, CustomError::VotingClosed);
+    let ix = dao.publish_tally_ix().await;
+    dao.process(&[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn rejects_tally_that_does_not_decrypt_the_sums() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    dao.process(&[ix], &[]).await.unwrap();
+    let ix = dao.vote_ix(false).await;
+    dao.process(&[ix], &[]).await.unwrap();
+    let election: Election = dao.account(election_address(&dao.payer())).await;
+    let no_proof = create_decryption_proof(&election.encrypted_no, 100, &dao.election_secret);
+
+    // Claiming the no votes as yes votes
+    let yes_proof = create_decryption_proof(&election.encrypted_yes, 100, &dao.election_secret);
+    dao.end_voting().await;
+    let ix = dao.publish_tally_with_ix(100, 100, yes_proof, no_proof.clone());
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidProof);
+
+    // A correct value proven with a secret other than the election's
+    let (_, other_secret) = create_keypair();
+    let yes_proof = create_decryption_proof(&election.encrypted_yes, 0, &other_secret);
+    let ix = dao.publish_tally_with_ix(0, 100, yes_proof, no_proof.clone());
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidProof);
+
+    // Proofs swapped between the sums
+    let yes_proof = create_decryption_proof(&election.encrypted_yes, 0, &dao.election_secret);
+    let ix = dao.publish_tally_with_ix(0, 100, no_proof.clone(), yes_proof.clone());
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidProof);
+
+    let ix = dao.publish_tally_with_ix(0, 100, yes_proof, no_proof);
+    dao.process(&[ix], &[]).await.unwrap();
+}
+
+#[test]
+fn ciphertext_arithmetic_is_homomorphic() {
+    let (public_key, secret) = create_keypair();
+    let yes = perform_encryption(true, &public_key);
+    let no = perform_encryption(false, &public_key);
+
+    let sum = yes.checked_mul(5).unwrap().checked_add(&no.checked_mul(9).unwrap()).unwrap();
+    assert_eq!(decrypt_tally(&sum, &secret, 20), Some(5));
+    let flipped = ElGamalCiphertext::trivial(5).checked_sub(&yes.checked_mul(5).unwrap()).unwrap();
+    assert_eq!(decrypt_tally(&flipped, &secret, 20), Some(0));
+
+    let malformed = ElGamalCiphertext { component1: [0xff; 32], component2: yes.component2 };
+    assert_eq!(yes.checked_add(&malformed), None);
+    assert_eq!(malformed.checked_mul(2), None);
+}
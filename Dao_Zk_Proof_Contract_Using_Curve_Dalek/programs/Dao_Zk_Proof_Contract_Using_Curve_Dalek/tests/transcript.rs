@@ -0,0 +1,79 @@
+mod common;
+
+use common::*;
+use curve25519_dalek::scalar::Scalar;
+use dao_zk_proof_contract_using_curve_dalek::errors::CustomError;
+use dao_zk_proof_contract_using_curve_dalek::zk_proof::*;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+
+#[test]
+fn proofs_are_bound_to_every_context_field() {
+    let (public_key, _) = create_keypair();
+    let context = offline_context();
+    let (ciphertext, proof) = ballot(true, 1, &public_key, &context);
+    assert!(validate_vote_proof(&proof, &ciphertext, 1, &public_key, &context));
+
+    let moved = [
+        BallotContext { program_id: Pubkey::new_unique(), ..context.clone() },
+        BallotContext { election: Pubkey::new_unique(), ..context.clone() },
+        BallotContext { election_id: context.election_id + 1, ..context.clone() },
+        BallotContext { voter: Pubkey::new_unique(), ..context.clone() },
+    ];
+    for other in &moved {
+        assert!(!validate_vote_proof(&proof, &ciphertext, 1, &public_key, other));
+    }
+}
+
+#[test]
+fn proofs_do_not_follow_rerandomized_ciphertexts() {
+    let (public_key, _) = create_keypair();
+    let context = offline_context();
+    let (ciphertext, proof) = ballot(false, 1, &public_key, &context);
+
+    // Adding an encryption of zero keeps the plaintext but changes the ciphertext
+    let zero = encrypt_with_randomness(false, &Scalar::random(&mut rand::rngs::OsRng), &public_key);
+    let rerandomized = ciphertext.checked_add(&zero).unwrap();
+    assert!(!validate_vote_proof(&proof, &rerandomized, 1, &public_key, &context));
+
+    // Another ballot with the same vote
+    let (other, _) = ballot(false, 1, &public_key, &context);
+    assert!(!validate_vote_proof(&proof, &other, 1, &public_key, &context));
+}
+
+#[tokio::test]
+async fn rejects_ballot_replayed_by_another_voter() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    dao.process(&[ix], &[]).await.unwrap();
+
+    let (ciphertext, proof) = dao.ballot_for(dao.payer(), true, dao.balance).await;
+    let ix = dao.vote_with_ix(ciphertext.clone(), proof.clone());
+    dao.process(&[ix], &[]).await.unwrap();
+
+    // Copying the payer's ballot from the transaction doesn't let another wallet cast it
+    let (voter, token_account) = dao.add_voter(50);
+    let ix = dao.vote_as_ix(voter.pubkey(), token_account, ciphertext, proof);
+    assert_custom_error(dao.process(&[ix], &[&voter]).await, CustomError::InvalidProof);
+}
+
+#[tokio::test]
+async fn rejects_ballot_proven_for_another_election() {
+    let mut dao = start(100).await;
+    let ix = dao.new_polling_ix("newSymbol", "NEW");
+    dao.process(&[ix], &[]).await.unwrap();
+
+    // Same key and voter, but an election id other than the open one
+    let mut context = dao.ballot_context(dao.payer()).await;
+    context.election_id += 1;
+    let (ciphertext, proof) = ballot(true, dao.balance, &dao.election_key, &context);
+    let ix = dao.vote_with_ix(ciphertext, proof);
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidProof);
+
+    // And one proven for a different election account
+    let mut context = dao.ballot_context(dao.payer()).await;
+    context.election = election_address(&Pubkey::new_unique());
+    let (ciphertext, proof) = ballot(true, dao.balance, &dao.election_key, &context);
+    let ix = dao.vote_with_ix(ciphertext, proof);
+    assert_custom_error(dao.process(&[ix], &[]).await, CustomError::InvalidProof);
+}
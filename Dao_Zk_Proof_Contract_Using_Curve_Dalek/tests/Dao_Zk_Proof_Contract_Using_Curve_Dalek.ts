@@ -44,9 +44,17 @@ describe("dao_voting", () => {
       const proposalVoting = "newSymbol";
       const value = "NEW";
       const additionalValue = "Additional Value";
+      // Ballots are encrypted to this key; the tally authority keeps the secret
+      const encryptionKey = { compressedPoint: Array(32).fill(1) }; // Replace with an actual ElGamal public key
+      // Public weights: ballots are checked against the token balance, with no weight commitment
+      const hiddenWeights = false;
+      // A yes/no poll; 2 to MAX_OPTIONS opens a multi-option poll voted on with `voteOptions`
+      const options = 0;
+      // Seconds ballots are taken for; the tally can only be published once they are over
+      const votingPeriod = new anchor.BN(60);
 
       const tx = await program.methods
-        .newPolling(token, proposalVoting, value, additionalValue)
+        .newPolling(token, proposalVoting, value, additionalValue, encryptionKey, hiddenWeights, options, votingPeriod)
         .accounts({
           election: electionPda,
           authority: provider.wallet.publicKey,
@@ -74,16 +82,28 @@ describe("dao_voting", () => {
 
   it("Casts a vote", async () => {
     try {
-      const vote = true;
+      // `vote × balance`, encrypted and proven client-side (see `encrypt_value` and `create_vote_proof`),
+      // with a `BallotContext` naming this program, the election and the voting wallet
+      const ciphertext = { component1: Array(32).fill(0), component2: Array(32).fill(0) };
+      const proof = {
+        commitment0_1: Array(32).fill(0),
+        commitment0_2: Array(32).fill(0),
+        commitment1_1: Array(32).fill(0),
+        commitment1_2: Array(32).fill(0),
+        challenge0: Array(32).fill(0),
+        response0: Array(32).fill(0),
+        response1: Array(32).fill(0),
+      };
 
       const tx = await program.methods
-        .vote(vote)
+        .vote(ciphertext, proof)
         .accounts({
           election: electionPda,
           authority: provider.wallet.publicKey,
           tokenAccount: provider.wallet.publicKey, // Replace with actual token account
           changableTokenAccount: changableTokenAccountPda, // Replace with actual changable token account
           user: userStatePda,
+          weightCommitment: null, // Only hidden-weight elections take one, from `commitWeight`
           verifyingKey: Keypair.generate().publicKey, // Replace with actual verifying key account
           systemProgram: SystemProgram.programId,
         })
@@ -103,6 +123,34 @@ describe("dao_voting", () => {
     }
   });
 
+  it("Publishes the tally", async () => {
+    try {
+      // Decrypted and proven by the holder of the election secret (see `create_decryption_proof`),
+      // after the voting period has ended
+      const decryptionProof = {
+        commitment1: Array(32).fill(0),
+        commitment2: Array(32).fill(0),
+        response: Array(32).fill(0),
+      };
+
+      const tx = await program.methods
+        .publishTally(new anchor.BN(1), new anchor.BN(0), decryptionProof, decryptionProof)
+        .accounts({
+          election: electionPda,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      console.log("Publish tally transaction signature", tx);
+
+      const electionAccount = await program.account.election.fetch(electionPda);
+      expect(electionAccount.tallyPublished).to.be.true;
+    } catch (error) {
+      console.error("Error during tally publication:", error);
+      throw error;
+    }
+  });
+
   it("Summarizes the results", async () => {
     try {
       const tx = await program.methods